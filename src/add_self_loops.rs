@@ -1,15 +1,23 @@
 use std::collections::{HashMap, HashSet};
-use std::fs as stdfs;
-use crate::types::{OCEL, ProcessForest, TreeNode};
-use crate::build_relations_fns;
+use crate::types::{ObjectTrace, ProcessForest, TreeNode};
+use crate::event_log_index::{ActivityId, EventLogIndex, ObjectTypeId, TieBreak};
+use crate::divergence_free_dfg::waiting_seconds;
 use crate::interaction_patterns;
-use uuid::Uuid;
+use crate::ocel_import;
+use crate::node_ids::assign_missing_node_ids;
+use itertools::Itertools;
 
 /// Adds self-loops to a DFG and prints it
 /// 
-/// This function takes a DFG (Directly-Follows Graph), OCPT (Process Forest), and file name and prints their structure,
-/// including all nodes and edges. Returns the modified OCPT with self-loops added and the list of self-loop activities.
-pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessForest, file_name: &str) -> (ProcessForest, Vec<String>) {
+/// This function takes a DFG (Directly-Follows Graph), OCPT (Process Forest), the log it was discovered from and its divergent
+/// object types per activity, and prints their structure, including all nodes and edges. Returns the modified OCPT with
+/// self-loops added and the list of self-loop activities.
+pub fn add_self_loops(
+    dfg: &HashMap<(String, String), usize>,
+    ocpt: &ProcessForest,
+    index: &EventLogIndex,
+    divergent_objects: &HashMap<String, Vec<String>>,
+) -> (ProcessForest, Vec<String>) {
     println!("Checking for self-loops in DFG...");
     
     // Extract unique activities (nodes) from the DFG
@@ -27,6 +35,7 @@ pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessFore
     // create variable self_loop_activities and collect the from the self_loops
     let self_loop_activities: Vec<String> = self_loops.iter()
         .map(|((from, _), _)| from.clone())
+        .sorted()
         .collect();
     
     println!("Found {} self-loop(s): {:?}", self_loop_activities.len(), self_loop_activities);
@@ -40,7 +49,7 @@ pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessFore
     println!("Processing {} self-loop(s) and modifying OCPT...", self_loop_activities.len());
     
     // Get traces once outside the loop to avoid multiple calls
-    let all_traces = get_traces(file_name);
+    let all_traces = get_traces_from_index(index, divergent_objects);
     
     // Start with the original OCPT and progressively modify it
     let mut current_ocpt = ocpt.clone();
@@ -56,14 +65,14 @@ pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessFore
                 // Get all traces containing any of the descendants
                 let relevant_traces: Vec<_> = all_traces.iter()
                     .filter(|trace| {
-                        trace.iter().any(|event| self_loop_activity_siblings_and_its_decendants.contains(&event.1))
+                        trace.activities.iter().any(|activity| self_loop_activity_siblings_and_its_decendants.contains(activity))
                     })
                     .collect();
                 
                 // Check if self-loop activity is present in ALL of those traces
                 let self_loop_in_all_traces = relevant_traces.iter()
                     .all(|trace| {
-                        trace.iter().any(|event| &event.1 == self_loop_activity)
+                        trace.activities.contains(self_loop_activity)
                     });
                 
                 if self_loop_in_all_traces {
@@ -97,7 +106,7 @@ pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessFore
                     // Filter traces containing any activity from other_branch_activities_of_pseudo_root
                     all_traces.iter()
                         .filter(|trace| {
-                            trace.iter().any(|event| other_branch_activities_of_pseudo_root.contains(&event.1))
+                            trace.activities.iter().any(|activity| other_branch_activities_of_pseudo_root.contains(activity))
                         })
                         .collect()
                 };
@@ -109,7 +118,7 @@ pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessFore
                         .into_iter()
                         .filter(|trace| {
                             // Keep traces that do NOT contain any activity from self_loop_activity_ancestors_of_same_branch
-                            !trace.iter().any(|event| self_loop_activity_ancestors_of_same_branch.contains(&event.1))
+                            !trace.activities.iter().any(|activity| self_loop_activity_ancestors_of_same_branch.contains(activity))
                         })
                         .collect();
                 }
@@ -121,7 +130,7 @@ pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessFore
                         .into_iter()
                         .filter(|trace| {
                             // Keep traces that do NOT contain any activity from self_loop_activity_siblings_and_its_decendants
-                            !trace.iter().any(|event| self_loop_activity_siblings_and_its_decendants.contains(&event.1))
+                            !trace.activities.iter().any(|activity| self_loop_activity_siblings_and_its_decendants.contains(activity))
                         })
                         .collect();
                 }
@@ -133,7 +142,7 @@ pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessFore
                     // Check if self-loop activity is present in ALL of the relevant traces
                     let self_loop_in_all_relevant_traces = relevant_traces.iter()
                         .all(|trace| {
-                            trace.iter().any(|event| &event.1 == self_loop_activity)
+                            trace.activities.contains(self_loop_activity)
                         });
                     
                     if self_loop_in_all_relevant_traces {
@@ -152,79 +161,57 @@ pub fn add_self_loops(dfg: &HashMap<(String, String), usize>, ocpt: &ProcessFore
         }
     }
     
+    // The nodes added for the loops get ids, the others keep theirs
+    assign_missing_node_ids(&mut current_ocpt);
+
     // Return the final modified OCPT and the self-loop activities
     println!("Successfully processed and added {} self-loop(s) to OCPT.", processed_count);
     (current_ocpt, self_loop_activities)
 }
-pub fn get_traces(file_name: &str) -> Vec<Vec<(String, String, String, String, String)>> {
-    let file_path = format!("data/{}.json", file_name);
-
-    let file_content = stdfs::read_to_string(&file_path).unwrap();
-    let ocel: OCEL = serde_json::from_str(&file_content).unwrap();
-
-    let relations = build_relations_fns::build_relations(&ocel.events, &ocel.objects);
-
-    let (divergent_objects, con, rel, defi, all_activities, all_object_types) =
-        interaction_patterns::get_interaction_patterns(&relations, &ocel);
+pub fn get_traces(file_name: &str, tie_break: &TieBreak) -> Vec<ObjectTrace> {
+    // Only the relations are needed here, so skip the full event/object model
+    let index = EventLogIndex::from_compact(ocel_import::load_compact_ocel(file_name).unwrap(), tie_break);
+    let (divergent_objects, _con, _rel, _defi, _all_activities, _all_object_types) =
+        interaction_patterns::get_interaction_patterns(&index);
+    get_traces_from_index(&index, &divergent_objects)
+}
 
-    // Group relations by object ID (oid)
-    let mut grouped_relations: HashMap<String, Vec<(String, String, String, String, String)>> = HashMap::new();
-    
-    for relation in relations {
-        grouped_relations
-            .entry(relation.3.clone()) // oid is at index 3
-            .or_insert_with(Vec::new)
-            .push(relation);
-    }
+/// Same as [`get_traces`], for a log that is already loaded (and possibly projected or filtered)
+/// with its divergent object types per activity
+pub fn get_traces_from_index(index: &EventLogIndex, divergent_objects: &HashMap<String, Vec<String>>) -> Vec<ObjectTrace> {
+    let divergent_objects = index.object_types_per_activity(divergent_objects);
 
     // Step 1: Create empty array called traces
-    let mut traces: Vec<Vec<(String, String, String, String, String)>> = Vec::new();
+    let mut traces: Vec<ObjectTrace> = Vec::new();
 
 
-    // Step 2 & 3: Sort every list inside grouped_relations by timestamp and put in traces
-    // (Commented out for testing with hardcoded data)
+    // Step 2 & 3: Take the relations of every object (already sorted by timestamp) as a trace
     
-    for (_oid, mut relations_group) in grouped_relations {
-        // Sort by timestamp (index 2)
-        relations_group.sort_by(|a, b| a.2.cmp(&b.2));
-        
-        // The relations_group already contains owned data, no need to clone
-        let sorted_trace: Vec<(String, String, String, String, String)> = relations_group;
-        
+    for sorted_trace in index.relations_by_object() {
         // Check if any two event types in the trace have common divergent objects
         let mut should_skip_trace = false;
-        let mut trace_object_types: HashSet<String> = HashSet::new();
-        let mut trace_event_types: HashSet<String> = HashSet::new();
+        let mut trace_object_types: HashSet<ObjectTypeId> = HashSet::new();
+        let mut trace_event_types: HashSet<ActivityId> = HashSet::new();
         
         // Collect all object types and event types in this trace
         for event in &sorted_trace {
-            trace_object_types.insert(event.4.clone()); // object_type is at index 4
-            trace_event_types.insert(event.1.clone()); // event_type is at index 1
+            trace_object_types.insert(event.object_type);
+            trace_event_types.insert(event.activity);
         }
         
         // Check if any two event types in the trace has any common divergent_objects, 
         // and the trace in question is dealing with those common divergent object types
-        let event_types_vec: Vec<String> = trace_event_types.into_iter().collect();
+        let event_types_vec: Vec<ActivityId> = trace_event_types.into_iter().collect();
         'outer: for i in 0..event_types_vec.len() {
             for j in (i + 1)..event_types_vec.len() {
-                let event_type1 = &event_types_vec[i];
-                let event_type2 = &event_types_vec[j];
+                let divergent_types1 = &divergent_objects[event_types_vec[i] as usize];
+                let divergent_types2 = &divergent_objects[event_types_vec[j] as usize];
                 
-                if let (Some(divergent_types1), Some(divergent_types2)) = 
-                    (divergent_objects.get(event_type1), divergent_objects.get(event_type2)) {
-                    
-                    // Find common divergent object types between the two event types
-                    let common_divergent_types: HashSet<_> = divergent_types1
-                        .iter()
-                        .filter(|&dt| divergent_types2.contains(dt))
-                        .collect();
-                    
-                    // Check if any of the common divergent types are present in the trace
-                    for common_divergent_type in &common_divergent_types {
-                        if trace_object_types.contains(*common_divergent_type) {
-                            should_skip_trace = true;
-                            break 'outer;
-                        }
+                // Check if any of the common divergent types are present in the trace
+                for common_divergent_type in divergent_types1.iter().filter(|&dt| divergent_types2.contains(dt)) {
+                    if trace_object_types.contains(common_divergent_type) {
+                        should_skip_trace = true;
+                        break 'outer;
                     }
                 }
             }
@@ -232,7 +219,11 @@ pub fn get_traces(file_name: &str) -> Vec<Vec<(String, String, String, String, S
         
         // Only push the trace if it doesn't have divergent object types
         if !should_skip_trace {
-            traces.push(sorted_trace);
+            traces.push(ObjectTrace {
+                object_type: index.object_type(sorted_trace[0].object_type).to_string(),
+                activities: sorted_trace.iter().map(|event| index.activity(event.activity).to_string()).collect(),
+                throughput_time: waiting_seconds(sorted_trace[0], sorted_trace[sorted_trace.len() - 1]),
+            });
         }
     }
     
@@ -304,13 +295,13 @@ fn modify_tree_with_redo(node: &mut TreeNode, self_loop_activity: &str, self_loo
         if child.label == self_loop_activity {
             // Found the self-loop activity as a direct child
             let self_loop_node = TreeNode {
-                id: Uuid::new_v4().to_string(),
+                id: String::new(),
                 label: self_loop_activity.to_string(),
                 children: vec![],
             };
             
             let tau_node = TreeNode {
-                id: Uuid::new_v4().to_string(),
+                id: String::new(),
                 label: "tau".to_string(),
                 children: vec![],
             };
@@ -322,7 +313,7 @@ fn modify_tree_with_redo(node: &mut TreeNode, self_loop_activity: &str, self_loo
             };
             
             let redo_node = TreeNode {
-                id: Uuid::new_v4().to_string(),
+                id: String::new(),
                 label: "redo".to_string(),
                 children: redo_children,
             };
@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet, VecDeque};
+use itertools::Itertools;
 use log::info;
 
 #[derive(Debug, Clone)]
@@ -143,7 +144,8 @@ pub fn best_exclusive_cut(
         return (0, Vec::new(), all_activities.clone(), HashSet::new(), dfg.clone());
     }
     
-    let activities: Vec<String> = all_activities.iter().cloned().collect();
+    let mut activities: Vec<String> = all_activities.iter().cloned().collect();
+    activities.sort();
     let mut min_cost = usize::MAX;
     let mut best_cut_edges: Vec<(String, String, usize)> = Vec::new();
     let mut best_set1 = HashSet::new();
@@ -177,8 +179,9 @@ pub fn best_exclusive_cut(
             graph.add_edge(activity_t.clone(), sink.clone(), inf_capacity);
 
             
-            // Add all DFG edges as undirected edges in the graph
-            for ((from, to), weight) in dfg {
+            // Add all DFG edges as undirected edges in the graph; in edge order, as the later of
+            // a -> b and b -> a sets the capacity
+            for ((from, to), weight) in dfg.iter().sorted() {
                 // Only add if both nodes are in our activity set
                 if all_activities.contains(from) && all_activities.contains(to) {
                     graph.add_edge(from.clone(), to.clone(), *weight);
@@ -225,6 +228,7 @@ pub fn best_exclusive_cut(
                     total_cut_cost += cost;
                 }
             }
+            cut_edges.sort();
             
             //info!("Cut cost: {}, Cut edges: {:?}", total_cut_cost, cut_edges);
             //info!("Set1: {:?}, Set2: {:?}", set1, set2);
@@ -1,5 +1,7 @@
 use std::collections::{HashMap, HashSet};
+use itertools::Itertools;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 
 #[derive(Debug, Clone)]
 pub struct PartitionResult {
@@ -19,13 +21,16 @@ struct Solution {
     edges_to_add: Vec<(String, String)>,
 }
 
+/// Heuristic parallel cut: local search from random partitions. The restarts draw from `seed`
+/// (0 if none is given), so the same seed gives the same cut.
 pub fn best_parallel_cut(
     dfg: &HashMap<(String, String), usize>,
     all_activities: &HashSet<String>,
+    seed: Option<u64>,
 ) -> PartitionResult {
     println!("Starting bipartite partition for {} activities", all_activities.len());
     
-    let activities: Vec<String> = all_activities.iter().cloned().collect();
+    let activities: Vec<String> = all_activities.iter().cloned().sorted().collect();
     let n = activities.len();
     
     // Create edge set for fast lookup (ignoring costs)
@@ -36,7 +41,7 @@ pub fn best_parallel_cut(
     let min_set_size = std::cmp::max(1, n / 4);
     
     let mut best_solution: Option<Solution> = None;
-    let mut rng = thread_rng();
+    let mut rng = StdRng::seed_from_u64(seed.unwrap_or_default());
     
     println!("Performing {} random restarts", num_restarts);
     
@@ -73,7 +78,7 @@ pub fn best_parallel_cut(
 fn generate_initial_partition(
     activities: &[String],
     min_set_size: usize,
-    rng: &mut ThreadRng,
+    rng: &mut StdRng,
 ) -> Solution {
     let n = activities.len();
     let max_set_size = n - min_set_size;
@@ -116,6 +121,7 @@ fn local_search_improvement(
         let all_activities: Vec<String> = solution.set1.iter()
             .chain(solution.set2.iter())
             .cloned()
+            .sorted()
             .collect();
         
         for activity in &all_activities {
@@ -186,8 +192,8 @@ fn final_optimization(
     edge_set: &HashSet<(String, String)>,
 ) -> Solution {
     // Try activity swaps between sets
-    let set1_vec: Vec<String> = solution.set1.iter().cloned().collect();
-    let set2_vec: Vec<String> = solution.set2.iter().cloned().collect();
+    let set1_vec: Vec<String> = solution.set1.iter().cloned().sorted().collect();
+    let set2_vec: Vec<String> = solution.set2.iter().cloned().sorted().collect();
     
     let swap_limit = std::cmp::min(8, std::cmp::min(set1_vec.len(), set2_vec.len()));
     
@@ -270,6 +276,7 @@ fn get_edges_to_add(
             }
         }
     }
+    edges_to_add.sort();
     
     edges_to_add
 }
@@ -16,7 +16,8 @@
         dfg: &HashMap<(String, String), usize>,
         all_activities: &HashSet<String>,
     ) -> PartitionResult {
-        let activities: Vec<String> = all_activities.iter().cloned().collect();
+        let mut activities: Vec<String> = all_activities.iter().cloned().collect();
+        activities.sort();
         let n = activities.len();
         
         println!("Starting exhaustive search for {} activities", n);
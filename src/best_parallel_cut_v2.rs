@@ -127,7 +127,8 @@ pub fn best_parallel_cut_v2(
     dfg: &HashMap<(String, String), usize>,
     all_activities: &HashSet<String>,
 ) -> PartitionResult {
-    let activities: Vec<String> = all_activities.iter().cloned().collect();
+    let mut activities: Vec<String> = all_activities.iter().cloned().collect();
+    activities.sort();
     let num_activities = activities.len();
 
     if num_activities < 2 {
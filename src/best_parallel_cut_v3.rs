@@ -16,7 +16,8 @@ pub fn best_parallel_cut_v3(
     let (missing_dfg, edge_to_missing_map) = create_missing_dfg(dfg, all_activities, cost_to_add_edges);
 
     // Try each activity as a potential source for min-cut
-    let activities: Vec<String> = all_activities.iter().cloned().collect();
+    let mut activities: Vec<String> = all_activities.iter().cloned().collect();
+    activities.sort();
     
     for i in 0..activities.len() {
         for j in (i + 1)..activities.len() {
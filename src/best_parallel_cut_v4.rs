@@ -25,8 +25,10 @@ pub fn best_parallel_cut_v4(
     let (base_missing_dfg, edge_to_missing_map) = create_missing_dfg(dfg, all_activities, cost_to_add_edges);
 
     // Convert sets to vecs for indexing
-    let start_vec: Vec<String> = start_activities.iter().cloned().collect();
-    let end_vec: Vec<String> = end_activities.iter().cloned().collect();
+    let mut start_vec: Vec<String> = start_activities.iter().cloned().collect();
+    let mut end_vec: Vec<String> = end_activities.iter().cloned().collect();
+    start_vec.sort();
+    end_vec.sort();
 
     // 2. Iterate through specific Start/End combinations
     // We need: Set1 having (s1, e1) and Set2 having (s2, e2)
@@ -286,6 +288,7 @@ fn max_flow_min_cut(
             }
         }
     }
+    added_edges.sort();
     
     (max_flow, set1, set2, added_edges)
 }
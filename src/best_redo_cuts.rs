@@ -221,6 +221,8 @@ fn remove_edges_for_redo(
         }
     }
     
+    edges_to_remove.sort();
+
     // Remove the collected edges
     for (from, to, _cost) in &edges_to_remove {
         new_dfg.remove(&(from.clone(), to.clone()));
@@ -331,6 +333,7 @@ fn add_edges_for_redo(
             }
         }
     }
+    edges_added.sort();
     
     (new_dfg, edges_added, total_cost)
 }
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use crate::cost_to_cut::is_reachable;
 use crate::cost_to_cut::to_be_non_reachable;
+use itertools::Itertools;
 use log::info;
 
 pub fn best_sequence_cut(
@@ -25,8 +26,9 @@ pub fn best_sequence_cut(
 
     
     // create a nested loop for every pair of activities
-    for activity1 in all_activities {
-        for activity2 in all_activities {
+    let activities: Vec<&String> = all_activities.iter().sorted().collect();
+    for &activity1 in &activities {
+        for &activity2 in &activities {
             if activity1 != activity2 {
                 // Call the function to find the minimum edge cut
                 // this is taking around 70ms
@@ -81,9 +83,9 @@ pub fn best_sequence_cut(
                 let mut added_edges: Vec<(String, String, usize)> = Vec::new();
                 let mut no_of_added_edges = 0;
 
-                // Adding necessary edges
-                for s1 in &set1 {
-                    for s2 in &set2 {
+                // Adding necessary edges; each one changes what is reachable, so go in order
+                for s1 in set1.iter().sorted() {
+                    for s2 in set2.iter().sorted() {
                         if !is_reachable(&new_dfg, s1, s2) {
                             // Add edge and update cost
                             let edge = (s1.clone(), s2.clone());
@@ -137,7 +137,8 @@ pub fn best_exclusive_cut(
     // Simple approach: try all possible 2^n partitions and find minimum cost
     // This is exponential but works correctly for small inputs
     
-    let activities: Vec<String> = all_activities.iter().cloned().collect();
+    let mut activities: Vec<String> = all_activities.iter().cloned().collect();
+    activities.sort();
     let n = activities.len();
     
     if n == 0 {
@@ -1,41 +1,15 @@
-use std::collections::HashMap;
+use crate::event_log_index::{EventLogIndex, TieBreak};
 use crate::types::{OCELEvent, OCELObject};
 
 
 
+/// Builds the relation table (event, activity, timestamp, object, object type) of a log.
+///
+/// Relationships to undeclared objects are skipped; `tie_break` orders events with equal timestamps.
 pub fn build_relations(
-    events: &Vec<OCELEvent>,
-    objects: &Vec<OCELObject>,
-) -> Vec<(String, String, String, String, String)> {
-
-    // Format (event_id, event_type, timestamp, object_id, object_type)
-
-    let mut relations = Vec::new();
-
-    // Create a HashMap for quick object lookup
-    let object_map: HashMap<String, &OCELObject> = objects.iter()
-        .map(|obj| (obj.id.clone(), obj))
-        .collect();
-
-    for event in events {
-        for relationship in &event.relationships {
-            if let Some(object) = object_map.get(&relationship.object_id) {
-                relations.push((
-                    event.id.clone(),
-                    event.event_type.clone(),
-                    event.time.to_rfc3339(),
-                    relationship.object_id.clone(),
-                    object.object_type.clone(),
-                ));
-            }
-        }
-    }
-
-    // relations.sort(); 
-
-    // First sorting by event id, then by timestamp
-    relations.sort_by(|a, b| a.0.cmp(&b.0));
-    relations.sort_by(|a, b| a.2.cmp(&b.2));
-
-    relations
+    events: &[OCELEvent],
+    objects: &[OCELObject],
+    tie_break: &TieBreak,
+) -> EventLogIndex {
+    EventLogIndex::from_ocel(events, objects, tie_break)
 }
@@ -1,17 +1,14 @@
 
-use crate::types::{ProcessForest, TreeNode};
+use crate::types::{ObjectTrace, ProcessForest, TreeNode};
 use crate::add_self_loops::get_traces;
+use crate::event_log_index::TieBreak;
 use std::collections::{HashSet, HashMap};
 
-fn format_traces_for_precision(traces: Vec<Vec<(String, String, String, String, String)>>, self_loop_activities: &[String]) -> Vec<Vec<String>> {
+fn format_traces_for_precision(traces: Vec<ObjectTrace>, self_loop_activities: &[String]) -> Vec<Vec<String>> {
     // println!("Formatting {} traces for precision calculation...", traces.len());
     
     let formatted_traces: Vec<Vec<String>> = traces.into_iter()
-        .map(|trace| {
-            trace.into_iter()
-                .map(|(_, activity, _, _, _)| activity)
-                .collect()
-        })
+        .map(|trace| trace.activities)
         .collect();
     
     // println!("Formatted traces:");
@@ -116,7 +113,7 @@ pub fn conformance_checking_mine_precision(ocpt: &ProcessForest, self_loop_activ
     // }
 
     // Get traces and format them for precision calculation
-    let raw_traces = get_traces(file_name);
+    let raw_traces = get_traces(file_name, &TieBreak::default());
     let traces = format_traces_for_precision(raw_traces, &self_loop_activities);
 
     // Check which executions are present in traces
@@ -318,13 +315,13 @@ pub fn conformance_checking_mine_fitness(ocpt: &ProcessForest, file_name: &str)
     println!("Starting conformance checking...");
 
     // Get traces once outside the loop to avoid multiple calls
-    let all_traces = get_traces(file_name);
+    let all_traces = get_traces(file_name, &TieBreak::default());
     let total_traces = all_traces.len();
     let mut incorrect_traces = 0;
 
     for trace in &all_traces {
         if !ocpt.is_empty() {
-            if !check_node(&ocpt[0], trace) {
+            if !check_node(&ocpt[0], &trace.activities) {
                 incorrect_traces += 1;
             }
         }
@@ -343,7 +340,7 @@ pub fn conformance_checking_mine_fitness(ocpt: &ProcessForest, file_name: &str)
     }
 }
 
-fn check_node(node: &TreeNode, trace: &Vec<(String, String, String, String, String)>) -> bool {
+fn check_node(node: &TreeNode, trace: &[String]) -> bool {
     if node.label == "sequence" {
         if node.children.len() >= 2 {
             let left_activities = get_descendant_activities(&node.children[0]);
@@ -401,12 +398,12 @@ fn check_node(node: &TreeNode, trace: &Vec<(String, String, String, String, Stri
 }
 
 fn check_sequence(left_activities: &HashSet<String>, right_activities: &HashSet<String>, 
-                 trace: &Vec<(String, String, String, String, String)>) -> bool {
+                 trace: &[String]) -> bool {
     // Find all positions of left and right activities
     let mut left_positions = Vec::new();
     let mut right_positions = Vec::new();
 
-    for (i, (_, activity, _, _, _)) in trace.iter().enumerate() {
+    for (i, activity) in trace.iter().enumerate() {
         if left_activities.contains(activity) {
             left_positions.push(i);
         }
@@ -431,10 +428,10 @@ fn check_sequence(left_activities: &HashSet<String>, right_activities: &HashSet<
 }
 
 fn check_exclusive(left_activities: &HashSet<String>, right_activities: &HashSet<String>, 
-                  trace: &Vec<(String, String, String, String, String)>) -> bool {
+                  trace: &[String]) -> bool {
     // If any right activity and any left activity both exist in the trace, return false
-    let has_left = trace.iter().any(|(_, activity, _, _, _)| left_activities.contains(activity));
-    let has_right = trace.iter().any(|(_, activity, _, _, _)| right_activities.contains(activity));
+    let has_left = trace.iter().any(|activity| left_activities.contains(activity));
+    let has_right = trace.iter().any(|activity| right_activities.contains(activity));
 
     // Return false if both sides have activities (exclusive violation)
     !(has_left && has_right)
@@ -459,9 +456,9 @@ fn get_descendant_activities(node: &TreeNode) -> HashSet<String> {
     activities
 }
 
-fn trace_contains_any_activity(trace: &Vec<(String, String, String, String, String)>, 
+fn trace_contains_any_activity(trace: &[String], 
                               activities: &HashSet<String>) -> bool {
-    trace.iter().any(|(_, activity, _, _, _)| activities.contains(activity))
+    trace.iter().any(|activity| activities.contains(activity))
 }
 
 fn remove_consecutive_self_loops(trace: Vec<String>) -> Vec<String> {
@@ -492,7 +489,7 @@ pub fn find_fitness_and_precision(ocpt: &ProcessForest, file_name: &str) -> (usi
     println!("Starting find_fitness_and_precision...");
     
     // Get traces from the file
-    let raw_traces = get_traces(file_name);
+    let raw_traces = get_traces(file_name, &TieBreak::default());
     let total_traces = raw_traces.len();
 
     // // ------------ print all raw_traces
@@ -502,19 +499,7 @@ pub fn find_fitness_and_precision(ocpt: &ProcessForest, file_name: &str) -> (usi
     
     // Step 1: Format traces to extract only activity names
     let activity_traces: Vec<(String, Vec<String>)> = raw_traces.into_iter()
-        .map(|trace| {
-            let info = if let Some(first_el) = trace.first() {
-                first_el.4.clone()
-            } else {
-                String::new()
-            };
-
-            let activities = trace.into_iter()
-                .map(|(_, activity, _, _, _)| activity)
-                .collect();
-            
-            (info, activities)
-        })
+        .map(|trace| (trace.object_type, trace.activities))
         .collect();
 
     // // ------------ print all activity_traces
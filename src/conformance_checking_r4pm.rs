@@ -3,7 +3,7 @@ use process_mining::conformance::object_centric::object_centric_language_abstrac
 };
 
 use std::fs::File;
-use std::io::{Write, Read};
+use std::io::{BufReader, Write, Read};
 use process_mining::core::event_data::object_centric::linked_ocel::IndexLinkedOCEL;
 use process_mining::core::OCEL;
 use process_mining::core::process_models::object_centric::ocpt::OCPT;
@@ -67,10 +67,9 @@ pub fn build_ocpt_format_for_conformance_from_json(file_name: &str) -> OCPT {
 
 pub fn build_ocel_format_for_conformance_from_json(file_name: &str) -> OCEL {
     let file_path = format!("conformance_files/{}-ocel-data.json", file_name);
-    let mut file = File::open(&file_path).expect(&format!("Failed to open file: {}", file_path));
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).expect("Failed to read file");
+    let file = File::open(&file_path).expect(&format!("Failed to open file: {}", file_path));
 
-    let ocel: OCEL = serde_json::from_str(&contents).expect("Failed to parse OCEL JSON");
+    // Read straight from the file, the log can be large
+    let ocel: OCEL = serde_json::from_reader(BufReader::new(file)).expect("Failed to parse OCEL JSON");
     ocel
 }
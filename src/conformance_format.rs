@@ -1,45 +1,100 @@
 use std::collections::{HashMap, HashSet};
-use crate::types::{OCELEvent, OCELObject};
-use crate::conformance_types::{OCEL, OCELType, OCPT, OCPTNode, OCPTOperator, OCPTOperatorType, OCPTLeaf, OCPTLeafLabel};
+use crate::ocel_stream::{CompactOCEL, UNDECLARED};
+use crate::types::OCEL;
+use crate::conformance_types::{OCELType, OCPT, OCPTNode, OCPTOperator, OCPTOperatorType, OCPTLeaf, OCPTLeafLabel};
 use std::fs::File;
-use std::io::{Write, Read};
-use serde::Deserialize;
+use std::io::{BufReader, BufWriter, Write, Read};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 
 
+// Borrowed view of the log in the OCEL 2.0 JSON layout, so the conformance file can be
+// written without copying the log. Attributes are left out, the conformance checker only
+// looks at events, objects and their relationships.
+#[derive(Serialize)]
+struct ConformanceRelationship<'a> {
+    #[serde(rename = "objectId")]
+    object_id: &'a str,
+    qualifier: &'a str,
+}
+
+#[derive(Serialize)]
+struct ConformanceEvent<'a> {
+    id: &'a str,
+    #[serde(rename = "type")]
+    event_type: &'a str,
+    time: &'a DateTime<FixedOffset>,
+    attributes: [(); 0],
+    relationships: Vec<ConformanceRelationship<'a>>,
+}
+
+#[derive(Serialize)]
+struct ConformanceObject<'a> {
+    id: &'a str,
+    #[serde(rename = "type")]
+    object_type: &'a str,
+    attributes: [(); 0],
+    relationships: Vec<ConformanceRelationship<'a>>,
+}
+
+#[derive(Serialize)]
+struct ConformanceOCEL<'a> {
+    #[serde(rename = "eventTypes")]
+    event_types: Vec<OCELType>,
+    #[serde(rename = "objectTypes")]
+    object_types: Vec<OCELType>,
+    events: Vec<ConformanceEvent<'a>>,
+    objects: Vec<ConformanceObject<'a>>,
+}
+
+/// Writes the log in the layout expected by the conformance checker to
+/// `conformance_files/{file_name}-ocel-data.json`, dropping relationships to unknown objects.
 pub fn build_ocel_format_for_conformance(
-    events: &Vec<OCELEvent>,
-    objects: &Vec<OCELObject>,
+    compact: &CompactOCEL,
     file_name: &str
-) -> OCEL {
+) {
 
-    // Create a HashMap for quick object lookup
-    let object_map: HashMap<String, &OCELObject> = objects.iter()
-        .map(|obj| (obj.id.clone(), obj))
-        .collect();
+    let declared = |object: u32| compact.objects[object as usize].object_type != UNDECLARED;
+    let relationships = |relationships: &'_ [(u32, u32)]| -> Vec<ConformanceRelationship<'_>> {
+        relationships
+            .iter()
+            .filter(|(object, _)| declared(*object))
+            .map(|(object, qualifier)| ConformanceRelationship {
+                object_id: &compact.objects[*object as usize].id,
+                qualifier: &compact.qualifiers[*qualifier as usize],
+            })
+            .collect()
+    };
 
-    let mut new_events = Vec::new();
+    let mut new_events = Vec::with_capacity(compact.events.len());
     let mut event_types_names = HashSet::new();
 
-    for event in events {
-        let mut valid_relationships = Vec::new();
-        for relationship in &event.relationships {
-            if object_map.contains_key(&relationship.object_id) {
-                valid_relationships.push(relationship.clone());
-            }
-        }
-        
-        let mut new_event = event.clone();
-        new_event.relationships = valid_relationships;
-        new_events.push(new_event);
-        
-        event_types_names.insert(event.event_type.clone());
+    for event in &compact.events {
+        let event_type = compact.event_types[event.event_type as usize].as_str();
+        new_events.push(ConformanceEvent {
+            id: &event.id,
+            event_type,
+            time: &event.time,
+            attributes: [],
+            relationships: relationships(&event.relationships),
+        });
+
+        event_types_names.insert(event_type.to_string());
     }
 
     let mut object_types_names = HashSet::new();
-    for obj in objects {
-        object_types_names.insert(obj.object_type.clone());
+    let mut objects = Vec::with_capacity(compact.objects.len());
+    for obj in compact.objects.iter().filter(|obj| obj.object_type != UNDECLARED) {
+        let object_type = compact.object_types[obj.object_type as usize].as_str();
+        objects.push(ConformanceObject {
+            id: &obj.id,
+            object_type,
+            attributes: [],
+            relationships: relationships(&obj.relationships),
+        });
+        object_types_names.insert(object_type.to_string());
     }
 
     let event_types: Vec<OCELType> = event_types_names.into_iter()
@@ -50,32 +105,24 @@ pub fn build_ocel_format_for_conformance(
         .map(|name| OCELType { name, attributes: Vec::new() })
         .collect();
 
-    // Convert types to conformance types
-    let new_events_json = serde_json::to_value(&new_events).expect("Failed to serialize events");
-    let converted_events: Vec<crate::conformance_types::OCELEvent> = serde_json::from_value(new_events_json).expect("Failed to convert events");
-    
-    let objects_json = serde_json::to_value(&objects).expect("Failed to serialize objects");
-    let converted_objects: Vec<crate::conformance_types::OCELObject> = serde_json::from_value(objects_json).expect("Failed to convert objects");
-
-    let ocel = OCEL {
+    let ocel = ConformanceOCEL {
         event_types,
         object_types,
-        events: converted_events,
-        objects: converted_objects,
+        events: new_events,
+        objects,
     };
 
-    let json_string = serde_json::to_string(&ocel).expect("Failed to serialize OCEL to JSON");
     let file_path = format!("conformance_files/{}-ocel-data.json", file_name);
-    
+
     // Ensure the directory exists
     if let Some(parent) = std::path::Path::new(&file_path).parent() {
         std::fs::create_dir_all(parent).expect("Failed to create directory");
     }
 
-    let mut file = File::create(file_path).expect("Failed to create file");
-    file.write_all(json_string.as_bytes()).expect("Failed to write to file");
-
-    ocel
+    let file = File::create(file_path).expect("Failed to create file");
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, &ocel).expect("Failed to serialize OCEL to JSON");
+    writer.flush().expect("Failed to write to file");
 }
 
 pub fn saveInteractionPatterns(
@@ -150,11 +197,9 @@ pub fn build_ocpt_format_for_conformance_from_json(file_name: &str) -> OCPT {
 
 pub fn build_ocel_format_for_conformance_from_json(file_name: &str) -> OCEL {
     let file_path = format!("conformance_files/{}-ocel-data.json", file_name);
-    let mut file = File::open(&file_path).expect(&format!("Failed to open file: {}", file_path));
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).expect("Failed to read file");
+    let file = File::open(&file_path).expect(&format!("Failed to open file: {}", file_path));
 
-    let ocel: OCEL = serde_json::from_str(&contents).expect("Failed to parse OCEL JSON");
+    let ocel: OCEL = serde_json::from_reader(BufReader::new(file)).expect("Failed to parse OCEL JSON");
     ocel
 }
 
@@ -1,14 +1,16 @@
 use std::collections::{HashMap, HashSet};
 // use plotters::prelude::*;
+
+use crate::event_log_index::{ActivityId, EventLogIndex, ObjectTypeId, Relation};
   
 
 pub fn cost_of_adding_edge(
-    relations: &Vec<(String, String, String, String, String)>,
+    index: &EventLogIndex,
     divergent_objects: &HashMap<String, Vec<String>>,
     dfg: &HashMap<(String, String), usize>
 ) -> HashMap<(String, String), f64> {
 
-    // relations format: (event_id, event_type, timestamp, object_id, object_type)
+    // relations carry interned activity / object type ids, see EventLogIndex
     ////// from relations, build a list of traces
     
     println!("Using provided divergent objects:");
@@ -18,75 +20,48 @@ pub fn cost_of_adding_edge(
 
     // print all unique object type for debugging
     let mut object_types = HashSet::new();
-    for relation in relations {
-        object_types.insert(relation.4.clone()); // object_type is at index 4
+    for relation in &index.relations {
+        object_types.insert(index.object_type(relation.object_type));
     }       
     println!("Unique object types in relations:");
     for obj_type in &object_types { 
         println!("  {}", obj_type); 
     }   
 
-    // Group relations by object ID (oid)
-    let mut grouped_relations: HashMap<String, Vec<&(String, String, String, String, String)>> = HashMap::new();
-    
-    for relation in relations {
-        grouped_relations
-            .entry(relation.3.clone()) // oid is at index 3
-            .or_insert_with(Vec::new)
-            .push(relation);
-    }
+    // Divergent object types per activity id
+    let divergent_objects = index.object_types_per_activity(divergent_objects);
 
     // Step 1: Create empty array called traces
-    let mut traces: Vec<Vec<(String, String, String, String, String)>> = Vec::new();
+    let mut traces: Vec<Vec<&Relation>> = Vec::new();
 
 
-    // Step 2 & 3: Sort every list inside grouped_relations by timestamp and put in traces
-    // (Commented out for testing with hardcoded data)
+    // Step 2 & 3: Take the relations of every object (already sorted by timestamp) as a trace
     
-    for (_oid, mut relations_group) in grouped_relations {
-        // Sort by timestamp (index 2)
-        relations_group.sort_by(|a, b| a.2.cmp(&b.2));
-        
-        // Convert from references back to owned data and add to traces
-        let sorted_trace: Vec<(String, String, String, String, String)> = relations_group
-            .into_iter()
-            .map(|relation| relation.clone())
-            .collect();
-        
+    for sorted_trace in index.relations_by_object() {
         // Check if any two event types in the trace have common divergent objects
         let mut should_skip_trace = false;
-        let mut trace_object_types: HashSet<String> = HashSet::new();
-        let mut trace_event_types: HashSet<String> = HashSet::new();
+        let mut trace_object_types: HashSet<ObjectTypeId> = HashSet::new();
+        let mut trace_event_types: HashSet<ActivityId> = HashSet::new();
         
         // Collect all object types and event types in this trace
         for event in &sorted_trace {
-            trace_object_types.insert(event.4.clone()); // object_type is at index 4
-            trace_event_types.insert(event.1.clone()); // event_type is at index 1
+            trace_object_types.insert(event.object_type);
+            trace_event_types.insert(event.activity);
         }
         
         // Check if any two event types in the trace has any common divergent_objects, 
         // and the trace in question is dealing with those common divergent object types
-        let event_types_vec: Vec<String> = trace_event_types.into_iter().collect();
+        let event_types_vec: Vec<ActivityId> = trace_event_types.into_iter().collect();
         'outer: for i in 0..event_types_vec.len() {
             for j in (i + 1)..event_types_vec.len() {
-                let event_type1 = &event_types_vec[i];
-                let event_type2 = &event_types_vec[j];
+                let divergent_types1 = &divergent_objects[event_types_vec[i] as usize];
+                let divergent_types2 = &divergent_objects[event_types_vec[j] as usize];
                 
-                if let (Some(divergent_types1), Some(divergent_types2)) = 
-                    (divergent_objects.get(event_type1), divergent_objects.get(event_type2)) {
-                    
-                    // Find common divergent object types between the two event types
-                    let common_divergent_types: HashSet<_> = divergent_types1
-                        .iter()
-                        .filter(|&dt| divergent_types2.contains(dt))
-                        .collect();
-                    
-                    // Check if any of the common divergent types are present in the trace
-                    for common_divergent_type in &common_divergent_types {
-                        if trace_object_types.contains(*common_divergent_type) {
-                            should_skip_trace = true;
-                            break 'outer;
-                        }
+                // Check if any of the common divergent types are present in the trace
+                for common_divergent_type in divergent_types1.iter().filter(|&dt| divergent_types2.contains(dt)) {
+                    if trace_object_types.contains(common_divergent_type) {
+                        should_skip_trace = true;
+                        break 'outer;
                     }
                 }
             }
@@ -99,43 +74,19 @@ pub fn cost_of_adding_edge(
     }
     
 
-    // Normalised timestamp traces
-    let mut normalized_traces: Vec<Vec<(String, String, String, String, String)>> = Vec::new();
+    // Normalised timestamp traces: (activity, seconds since the first event of the trace)
+    let mut normalized_traces: Vec<Vec<(ActivityId, f64)>> = Vec::new();
     
     for trace in &traces {
-        if !trace.is_empty() {
-            // Get start_time from the first event
-            let start_time_str = &trace[0].2;
+        if let Some(first) = trace.first() {
+            let start_time = first.time;
             
-            // Parse the start time (format: 2023-11-16T12:48:30+00:00)
-            if let Ok(start_time) = start_time_str.parse::<chrono::DateTime<chrono::Utc>>() {
-                // Create normalized trace by subtracting start_time from every timestamp
-                let mut normalized_trace = Vec::new();
-                for event in trace {
-                    if let Ok(event_time) = event.2.parse::<chrono::DateTime<chrono::Utc>>() {
-                        // Calculate time difference in seconds from start
-                        let duration = event_time.signed_duration_since(start_time);
-                        let seconds_from_start = duration.num_seconds();
-                        
-                        // Create new event with normalized timestamp
-                        let normalized_event = (
-                            event.0.clone(),  // event_id
-                            event.1.clone(),  // event_type
-                            seconds_from_start.to_string(), // normalized timestamp as seconds from start
-                            event.3.clone(),  // object_id
-                            event.4.clone()   // object_type
-                        );
-                        normalized_trace.push(normalized_event);
-                    }
-                }
-                
-                if !normalized_trace.is_empty() {
-                    normalized_traces.push(normalized_trace);
-                }
-            } else {
-                // If parsing fails, use original trace
-                normalized_traces.push(trace.clone());
-            }
+            // Subtract start_time from every timestamp
+            let normalized_trace: Vec<(ActivityId, f64)> = trace
+                .iter()
+                .map(|event| (event.activity, event.time.signed_duration_since(start_time).num_seconds() as f64))
+                .collect();
+            normalized_traces.push(normalized_trace);
         }
     }
     
@@ -159,36 +110,26 @@ pub fn cost_of_adding_edge(
         }
     }
 
-    // Precompute: group all events by activity type and sort them by timestamp once
-    let mut events_per_activity: HashMap<String, Vec<(String, String, String, String, String)>> = HashMap::new();
+    // Precompute: group all normalized timestamps by activity and sort them once
+    let mut timestamps_per_activity: Vec<Vec<f64>> = vec![Vec::new(); index.activities.len()];
 
     for trace in &normalized_traces {
-        for event in trace {
-            // event.1 is the activity label
-            events_per_activity.entry(event.1.clone())
-                .or_default()
-                .push(event.clone());
+        for &(activity, seconds) in trace {
+            timestamps_per_activity[activity as usize].push(seconds);
         }
     }
 
-    // Sort each activity’s events by timestamp (event.2)
-    for events in events_per_activity.values_mut() {
-        events.sort_by(|event1, event2| {
-            let timestamp1: f64 = event1.2.parse().unwrap_or(0.0);
-            let timestamp2: f64 = event2.2.parse().unwrap_or(0.0);
+    let mut events_with_timestamps: HashMap<String, Vec<f64>> = HashMap::new();
+    for (activity, mut timestamps) in timestamps_per_activity.into_iter().enumerate() {
+        if timestamps.is_empty() {
+            continue;
+        }
+        timestamps.sort_by(|timestamp1, timestamp2| {
             timestamp1
-                .partial_cmp(&timestamp2)
+                .partial_cmp(timestamp2)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
-    }
-
-    // Pre-parse timestamps to avoid repeated parsing in inner loops
-    let mut events_with_timestamps: HashMap<String, Vec<f64>> = HashMap::new();
-    for (activity, events) in &events_per_activity {
-        let timestamps: Vec<f64> = events.iter()
-            .map(|event| event.2.parse().unwrap_or(0.0))
-            .collect();
-        events_with_timestamps.insert(activity.clone(), timestamps);
+        events_with_timestamps.insert(index.activity(activity as ActivityId).to_string(), timestamps);
     }
 
     // Probability of event occurring in a trace
@@ -199,22 +140,16 @@ pub fn cost_of_adding_edge(
     
     // Calculate probability that each activity occurs at least once in a trace
     for activity in &activities {
-        let mut traces_containing_activity = 0;
         let total_traces = normalized_traces.len();
         
         // Count traces that contain this activity at least once
-        for trace in &normalized_traces {
-            let mut activity_found = false;
-            for event in trace {
-                if &event.1 == activity { // event.1 is the activity type
-                    activity_found = true;
-                    break;
-                }
-            }
-            if activity_found {
-                traces_containing_activity += 1;
-            }
-        }
+        let traces_containing_activity = match index.activity_id(activity) {
+            Some(activity_id) => normalized_traces
+                .iter()
+                .filter(|trace| trace.iter().any(|&(a, _)| a == activity_id))
+                .count(),
+            None => 0,
+        };
         
         // Calculate probability as fraction of traces containing the activity
         let probability = if total_traces > 0 {
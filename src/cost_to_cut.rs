@@ -157,6 +157,7 @@ fn find_min_edge_cut(
             cut_weight += cap;
         }
     }
+    cut_edges.sort();
 
     (cut_edges.len(), cut_weight, cut_edges)
 }
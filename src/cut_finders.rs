@@ -0,0 +1,511 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::best_exclusive_cut::best_exclusive_cut;
+use crate::best_parallel_cut::best_parallel_cut;
+use crate::best_parallel_cut_exhaustive::best_parallel_cut_exhaustive;
+use crate::best_parallel_cut_v2::best_parallel_cut_v2;
+use crate::best_parallel_cut_v3::best_parallel_cut_v3;
+use crate::best_parallel_cut_v4::best_parallel_cut_v4;
+use crate::best_redo_cuts::best_redo_cut;
+use crate::best_sequence_cut::best_sequence_cut;
+use crate::best_sequence_cut_v2;
+use crate::good_cuts::best_possible_sequence_cut;
+
+/// What a cut finder searches a cut for
+pub struct CutInput<'a> {
+    /// DFG restricted to `activities`
+    pub dfg: &'a HashMap<(String, String), usize>,
+    pub activities: &'a HashSet<String>,
+    pub start_activities: &'a HashSet<String>,
+    pub end_activities: &'a HashSet<String>,
+    pub cost_to_add_edges: &'a HashMap<(String, String), f64>,
+    /// Seed of the randomised finders
+    pub seed: Option<u64>,
+}
+
+/// Cut a finder proposes: a partition into two non-empty sets, and the edges to add and
+/// remove so that the DFG (`new_dfg`) allows it
+#[derive(Debug, Clone)]
+pub struct FoundCut {
+    pub set1: HashSet<String>,
+    pub set2: HashSet<String>,
+    pub edges_to_be_added: Vec<(String, String, usize)>,
+    pub edges_to_be_removed: Vec<(String, String, usize)>,
+    pub total_cost: usize,
+    pub new_dfg: HashMap<(String, String), usize>,
+}
+
+/// A strategy for finding the cheapest cut of one type
+pub trait CutFinder: Sync {
+    /// Name requests select the finder by, the one of the function it wraps
+    fn name(&self) -> &'static str;
+    /// `exclusive`, `sequence`, `parallel` or `redo`
+    fn cut_type(&self) -> &'static str;
+    /// Best cut the strategy finds, `None` if it finds no partition
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut>;
+}
+
+/// Every cut finder, in the order they are listed to clients
+pub static CUT_FINDERS: &[&dyn CutFinder] = &[
+    &BestExclusiveCut,
+    &ExhaustiveExclusiveCut,
+    &BestSequenceCut,
+    &GoodCuts,
+    &BestParallelCutV4,
+    &BestParallelCut,
+    &BestParallelCutV2,
+    &BestParallelCutV3,
+    &BestParallelCutExhaustive,
+    &BestRedoCut,
+];
+
+// Activities up to which the finders trying every partition are run
+const MAX_EXHAUSTIVE_ACTIVITIES: usize = 20;
+
+/// Cut finder of every cut type a discovery step uses
+#[derive(Clone, Copy)]
+pub struct CutStrategies {
+    pub exclusive: &'static dyn CutFinder,
+    pub sequence: &'static dyn CutFinder,
+    pub parallel: &'static dyn CutFinder,
+    pub redo: &'static dyn CutFinder,
+    pub seed: Option<u64>,
+}
+
+impl Default for CutStrategies {
+    fn default() -> Self {
+        CutStrategies {
+            exclusive: &BestExclusiveCut,
+            sequence: &BestSequenceCut,
+            parallel: &BestParallelCutV4,
+            redo: &BestRedoCut,
+            seed: None,
+        }
+    }
+}
+
+impl fmt::Debug for CutStrategies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CutStrategies")
+            .field("exclusive", &self.exclusive.name())
+            .field("sequence", &self.sequence.name())
+            .field("parallel", &self.parallel.name())
+            .field("redo", &self.redo.name())
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+impl CutStrategies {
+    /// Parses the finder names of the `exclusive_cut`, `sequence_cut`, `parallel_cut` and
+    /// `redo_cut` parameters; a missing name keeps the default finder of the cut type
+    pub fn from_params(
+        exclusive: Option<&str>,
+        sequence: Option<&str>,
+        parallel: Option<&str>,
+        redo: Option<&str>,
+        seed: Option<u64>,
+    ) -> Result<Self, String> {
+        let default = CutStrategies::default();
+        Ok(CutStrategies {
+            exclusive: finder_param("exclusive", exclusive, default.exclusive)?,
+            sequence: finder_param("sequence", sequence, default.sequence)?,
+            parallel: finder_param("parallel", parallel, default.parallel)?,
+            redo: finder_param("redo", redo, default.redo)?,
+            seed,
+        })
+    }
+
+    pub fn from_names(names: &CutStrategyNames) -> Result<Self, String> {
+        CutStrategies::from_params(
+            names.exclusive_cut.as_deref(),
+            names.sequence_cut.as_deref(),
+            names.parallel_cut.as_deref(),
+            names.redo_cut.as_deref(),
+            names.seed,
+        )
+    }
+
+    /// The finders, for responses to echo so that later steps use them too
+    pub fn names(&self) -> CutStrategyNames {
+        CutStrategyNames {
+            exclusive_cut: Some(self.exclusive.name().to_string()),
+            sequence_cut: Some(self.sequence.name().to_string()),
+            parallel_cut: Some(self.parallel.name().to_string()),
+            redo_cut: Some(self.redo.name().to_string()),
+            seed: self.seed,
+        }
+    }
+
+    /// Finders in the order cuts are suggested
+    pub fn finders(&self) -> [&'static dyn CutFinder; 4] {
+        [self.exclusive, self.sequence, self.parallel, self.redo]
+    }
+}
+
+/// Cut finders of a request by name, see [`CutStrategies`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CutStrategyNames {
+    #[serde(default)]
+    pub exclusive_cut: Option<String>,
+    #[serde(default)]
+    pub sequence_cut: Option<String>,
+    #[serde(default)]
+    pub parallel_cut: Option<String>,
+    #[serde(default)]
+    pub redo_cut: Option<String>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn finder_param(
+    cut_type: &str,
+    name: Option<&str>,
+    default: &'static dyn CutFinder,
+) -> Result<&'static dyn CutFinder, String> {
+    let Some(name) = name else {
+        return Ok(default);
+    };
+    let of_type: Vec<&'static dyn CutFinder> =
+        CUT_FINDERS.iter().copied().filter(|finder| finder.cut_type() == cut_type).collect();
+    match of_type.iter().find(|finder| finder.name() == name) {
+        Some(finder) => Ok(*finder),
+        None => Err(format!(
+            "Unknown {} cut finder '{}', expected {}",
+            cut_type,
+            name,
+            of_type.iter().map(|finder| finder.name()).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+// A cut if both sets are non-empty, edges in a fixed order
+fn found(
+    set1: HashSet<String>,
+    set2: HashSet<String>,
+    mut edges_to_be_added: Vec<(String, String, usize)>,
+    mut edges_to_be_removed: Vec<(String, String, usize)>,
+    total_cost: usize,
+    new_dfg: HashMap<(String, String), usize>,
+) -> Option<FoundCut> {
+    if set1.is_empty() || set2.is_empty() {
+        return None;
+    }
+    edges_to_be_added.sort();
+    edges_to_be_removed.sort();
+    Some(FoundCut { set1, set2, edges_to_be_added, edges_to_be_removed, total_cost, new_dfg })
+}
+
+// Edges a unit cost finder adds, with the weight it gives them in its DFG
+fn unit_edges(edges: Vec<(String, String)>) -> Vec<(String, String, usize)> {
+    edges.into_iter().map(|(from, to)| (from, to, 1)).collect()
+}
+
+fn without_edges(dfg: &HashMap<(String, String), usize>, edges: &[(String, String, usize)]) -> HashMap<(String, String), usize> {
+    let mut new_dfg = dfg.clone();
+    for (from, to, _) in edges {
+        new_dfg.remove(&(from.clone(), to.clone()));
+    }
+    new_dfg
+}
+
+fn too_many_for_exhaustive(name: &str, input: &CutInput) -> bool {
+    let too_many = input.activities.len() > MAX_EXHAUSTIVE_ACTIVITIES;
+    if too_many {
+        println!(
+            "Skipping {}: it tries every partition and {} activities exceed {}",
+            name,
+            input.activities.len(),
+            MAX_EXHAUSTIVE_ACTIVITIES
+        );
+    }
+    too_many
+}
+
+/// Minimum cut over every pair of activities kept apart, removing the edges between the sets
+pub struct BestExclusiveCut;
+
+impl CutFinder for BestExclusiveCut {
+    fn name(&self) -> &'static str {
+        "best_exclusive_cut"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "exclusive"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        let (cost, cut_edges, set1, set2, new_dfg) = best_exclusive_cut(input.dfg, input.activities);
+        found(set1, set2, Vec::new(), cut_edges, cost, new_dfg)
+    }
+}
+
+/// `best_sequence_cut_v2` tries every partition for the cheapest exclusive cut, despite the
+/// module name; only run up to 20 activities
+pub struct ExhaustiveExclusiveCut;
+
+impl CutFinder for ExhaustiveExclusiveCut {
+    fn name(&self) -> &'static str {
+        "best_sequence_cut_v2"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "exclusive"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        if too_many_for_exhaustive(self.name(), input) {
+            return None;
+        }
+        let (cost, cut_edges, set1, set2) = best_sequence_cut_v2::best_exclusive_cut(input.dfg, input.activities);
+        let cut_edges: Vec<(String, String, usize)> = cut_edges
+            .into_iter()
+            .map(|(from, to)| {
+                let weight = input.dfg.get(&(from.clone(), to.clone())).copied().unwrap_or(0);
+                (from, to, weight)
+            })
+            .collect();
+        let new_dfg = without_edges(input.dfg, &cut_edges);
+        found(set1, set2, Vec::new(), cut_edges, cost, new_dfg)
+    }
+}
+
+/// Cheapest sequence cut by removing backward edges and adding the missing forward ones
+pub struct BestSequenceCut;
+
+impl CutFinder for BestSequenceCut {
+    fn name(&self) -> &'static str {
+        "best_sequence_cut"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "sequence"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        let (cost, _, cut_edges, _, added_edges, set1, set2, new_dfg) =
+            best_sequence_cut(input.dfg, input.activities, input.cost_to_add_edges);
+        found(set1, set2, added_edges, cut_edges, cost, new_dfg)
+    }
+}
+
+/// Directed minimum cut over every source and sink activity, removing the edges back from
+/// the second set to the first
+pub struct GoodCuts;
+
+impl CutFinder for GoodCuts {
+    fn name(&self) -> &'static str {
+        "good_cuts"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "sequence"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        let result = match best_possible_sequence_cut(input.dfg, input.activities) {
+            Ok(result) => result,
+            Err(message) => {
+                println!("{} found no cut: {}", self.name(), message);
+                return None;
+            }
+        };
+        let cut_edges: Vec<(String, String, usize)> =
+            result.edges_to_cut.into_iter().map(|((from, to), weight)| (from, to, weight)).collect();
+        let new_dfg = without_edges(input.dfg, &cut_edges);
+        found(
+            result.set1.into_iter().collect(),
+            result.set2.into_iter().collect(),
+            Vec::new(),
+            cut_edges,
+            result.total_cost,
+            new_dfg,
+        )
+    }
+}
+
+/// Minimum cut on the costs of the missing edges, with a start and an end activity on both
+/// sides
+pub struct BestParallelCutV4;
+
+impl CutFinder for BestParallelCutV4 {
+    fn name(&self) -> &'static str {
+        "best_parallel_cut_v4"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "parallel"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        let (cost, _, added_edges, set1, set2, new_dfg) = best_parallel_cut_v4(
+            input.dfg,
+            input.activities,
+            input.cost_to_add_edges,
+            input.start_activities,
+            input.end_activities,
+        );
+        found(set1, set2, added_edges, Vec::new(), cost, new_dfg)
+    }
+}
+
+/// Randomised local search counting every missing edge once; seeded by the request
+pub struct BestParallelCut;
+
+impl CutFinder for BestParallelCut {
+    fn name(&self) -> &'static str {
+        "best_parallel_cut"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "parallel"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        if input.activities.len() < 2 {
+            return None;
+        }
+        let result = best_parallel_cut(input.dfg, input.activities, input.seed);
+        println!("{}: {} edges to add", self.name(), result.num_edges_added);
+        found(
+            result.set1,
+            result.set2,
+            unit_edges(result.edges_to_add),
+            Vec::new(),
+            result.minimum_cost,
+            result.new_dfg,
+        )
+    }
+}
+
+/// Global minimum cut counting every missing edge once
+pub struct BestParallelCutV2;
+
+impl CutFinder for BestParallelCutV2 {
+    fn name(&self) -> &'static str {
+        "best_parallel_cut_v2"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "parallel"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        let result = best_parallel_cut_v2(input.dfg, input.activities);
+        println!("{}: {} edges to add", self.name(), result.num_added_edges);
+        found(
+            result.set1,
+            result.set2,
+            unit_edges(result.added_edges),
+            Vec::new(),
+            result.min_cost,
+            result.new_dfg,
+        )
+    }
+}
+
+/// Minimum cut on the costs of the missing edges, without the start and end activity
+/// constraints of v4
+pub struct BestParallelCutV3;
+
+impl CutFinder for BestParallelCutV3 {
+    fn name(&self) -> &'static str {
+        "best_parallel_cut_v3"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "parallel"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        let (cost, _, added_edges, set1, set2, new_dfg) =
+            best_parallel_cut_v3(input.dfg, input.activities, input.cost_to_add_edges);
+        found(set1, set2, added_edges, Vec::new(), cost, new_dfg)
+    }
+}
+
+/// Tries every partition, counting every missing edge once; only run up to 20 activities
+pub struct BestParallelCutExhaustive;
+
+impl CutFinder for BestParallelCutExhaustive {
+    fn name(&self) -> &'static str {
+        "best_parallel_cut_exhaustive"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "parallel"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        if too_many_for_exhaustive(self.name(), input) {
+            return None;
+        }
+        let result = best_parallel_cut_exhaustive(input.dfg, input.activities);
+        println!("{}: {} edges to add", self.name(), result.num_edges_added);
+        found(
+            result.set1.into_iter().collect(),
+            result.set2.into_iter().collect(),
+            unit_edges(result.edges_to_add),
+            Vec::new(),
+            result.minimum_cost,
+            result.new_dfg,
+        )
+    }
+}
+
+/// Cheapest loop body and redo part, removing and adding edges around the start and end
+/// activities
+pub struct BestRedoCut;
+
+impl CutFinder for BestRedoCut {
+    fn name(&self) -> &'static str {
+        "best_redo_cut"
+    }
+
+    fn cut_type(&self) -> &'static str {
+        "redo"
+    }
+
+    fn find_cut(&self, input: &CutInput) -> Option<FoundCut> {
+        let (is_redo, cost, removed_edges, added_edges, _, _, set1, set2, new_dfg) = best_redo_cut(
+            input.dfg,
+            input.activities,
+            input.start_activities,
+            input.end_activities,
+            input.cost_to_add_edges,
+        );
+        if !is_redo {
+            return None;
+        }
+        found(set1, set2, added_edges, removed_edges, cost, new_dfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_finder_is_selectable_for_its_cut_type() {
+        for finder in CUT_FINDERS {
+            let name = Some(finder.name());
+            let strategies = match finder.cut_type() {
+                "exclusive" => CutStrategies::from_params(name, None, None, None, None),
+                "sequence" => CutStrategies::from_params(None, name, None, None, None),
+                "parallel" => CutStrategies::from_params(None, None, name, None, None),
+                _ => CutStrategies::from_params(None, None, None, name, None),
+            }
+            .unwrap();
+            assert!(strategies.finders().iter().any(|selected| selected.name() == finder.name()));
+        }
+    }
+
+    #[test]
+    fn finders_of_other_cut_types_are_rejected() {
+        let error = CutStrategies::from_params(None, Some("best_parallel_cut_v4"), None, None, None).unwrap_err();
+        assert_eq!(error, "Unknown sequence cut finder 'best_parallel_cut_v4', expected best_sequence_cut, good_cuts");
+    }
+}
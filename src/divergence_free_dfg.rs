@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use chrono::NaiveDateTime;
 use chrono::{DateTime, Utc};
 
@@ -7,107 +7,169 @@ use std::io::Write;
 use std::io::BufReader;
 use serde_json::Value;
 
+use crate::event_log_index::{ActivityId, EventId, EventLogIndex, ObjectTypeId, Relation};
+use crate::types::{DfgEdge, EdgeWeight, OCDfg, OCDfgEdge, ObjectTypeEdge, WaitingTimeStats, WeightedDfg};
+
+/// Builds the divergence-free DFG; every edge keeps its raw count, the number of objects
+/// it occurs for and its percentage of all edges (see [`weighted_dfg`]).
 pub fn get_divergence_free_graph_v2(
-    relations: &Vec<(String, String, String, String, String)>,
+    index: &EventLogIndex,
     divergent_objects: &HashMap<String, Vec<String>>,
-) -> (
-    HashMap<(String, String), usize>,
-    HashSet<String>,
-    HashSet<String>,
-) {
-
-    // // Convert to a serializable Vec of Vecs
-    let serializable: Vec<&(String, String, String, String, String)> = relations.iter().collect();
+) -> WeightedDfg {
+    get_oc_dfg(index, divergent_objects).merged()
+}
+
+/// Builds the divergence-free DFG as an [`OCDfg`], keeping for every edge how much of it
+/// each object type contributed, and the start and end activities per object type.
+pub fn get_oc_dfg(index: &EventLogIndex, divergent_objects: &HashMap<String, Vec<String>>) -> OCDfg {
+
+    // // Convert to a serializable Vec of Vecs (eid, activity, timestamp, oid, otype)
+    let serializable: Vec<(&str, &str, String, &str, &str)> = index.relations.iter()
+        .map(|r| (index.event_id(r.event), index.activity(r.activity), r.time.to_rfc3339(), index.object_id(r.object), index.object_type(r.object_type)))
+        .collect();
     let json = serde_json::to_string_pretty(&serializable).unwrap();
     let mut file = File::create("relations2.json").expect("Unable to create file");
     file.write_all(json.as_bytes()).expect("Unable to write data");
 
-    // Initialize return structures
-    let mut dfg: HashMap<(String, String), usize> = HashMap::new();
-    let mut start_activities: HashSet<String> = HashSet::new();
-    let mut end_activities: HashSet<String> = HashSet::new();
-
-    // Group relations by object ID (oid)
-    let mut grouped_relations: HashMap<String, Vec<&(String, String, String, String, String)>> = HashMap::new();
-    
-    for relation in relations {
-        grouped_relations
-            .entry(relation.3.clone()) // oid is at index 3
-            .or_insert_with(Vec::new)
-            .push(relation);
-    }
+    // Divergent object types per activity id
+    let divergent_objects = index.object_types_per_activity(divergent_objects);
 
-    // Sort object IDs to ensure deterministic processing order
-    let mut sorted_oids: Vec<_> = grouped_relations.keys().cloned().collect();
-    sorted_oids.sort();
-
-    // Process each group of relations for the same object ID in sorted order
-    for oid in sorted_oids {
-        let group = grouped_relations.get(&oid).unwrap();
+    // Initialize return structures
+    let mut dfg: HashMap<(ActivityId, ActivityId), (DfgEdge, HashMap<ObjectTypeId, ObjectTypeEdge>)> = HashMap::new();
+    let mut start_activities: HashSet<(ObjectTypeId, ActivityId)> = HashSet::new();
+    let mut end_activities: HashSet<(ObjectTypeId, ActivityId)> = HashSet::new();
+    // Waiting times in seconds per edge and object type
+    let mut waiting_times: HashMap<(ActivityId, ActivityId, ObjectTypeId), Vec<f64>> = HashMap::new();
+
+    // Process the relations of each object (already in time order)
+    for group in index.relations_by_object() {
         
         // Remove duplicates based on eid (keep first occurrence)
-        let mut seen_eids: HashSet<String> = HashSet::new();
-        let mut unique_relations: Vec<&(String, String, String, String, String)> = Vec::new();
+        let mut seen_eids: HashSet<EventId> = HashSet::new();
+        let mut unique_relations = Vec::new();
         
         for relation in group {
-            if seen_eids.insert(relation.0.clone()) { // eid is at index 0
+            if seen_eids.insert(relation.event) {
                 unique_relations.push(relation);
             }
         }
 
-        // Sort by timestamp (index 2)
-        unique_relations.sort_by(|a, b| a.2.cmp(&b.2));
-
         // Skip empty groups or groups with only one event
         if unique_relations.is_empty() {
             continue;
         }
 
+        // All relations of an object share its type
+        let otype = unique_relations[0].object_type;
+
+        // Edges already counted for this object
+        let mut object_edges: HashSet<(ActivityId, ActivityId)> = HashSet::new();
+
         // Add start activity (first event after sorting)
-        let start_activity = &unique_relations[0].1; // activity is at index 1
-        start_activities.insert(start_activity.clone());
+        start_activities.insert((otype, unique_relations[0].activity));
 
         // Add end activity (last event after sorting)
-        let end_activity = &unique_relations[unique_relations.len() - 1].1;
-        end_activities.insert(end_activity.clone());
+        end_activities.insert((otype, unique_relations[unique_relations.len() - 1].activity));
 
         // Create directly follows relationships
-        for i in 0..unique_relations.len() - 1 {
-            let current_relation = unique_relations[i];
-            let next_relation = unique_relations[i + 1];
-            
-            let current_activity = &current_relation.1;
-            let next_activity = &next_relation.1;
-            let current_otype = &current_relation.4;
+        for pair in unique_relations.windows(2) {
+            let current_activity = pair[0].activity;
+            let next_activity = pair[1].activity;
 
             // Check divergence condition before adding to DFG
-            let should_skip = if let (Some(current_divergent), Some(next_divergent)) = (
-                divergent_objects.get(current_activity),
-                divergent_objects.get(next_activity),
-            ) {
-                current_divergent.contains(current_otype) && next_divergent.contains(current_otype)
-            } else {
-                false
-            };
+            let should_skip = divergent_objects[current_activity as usize].contains(&otype)
+                && divergent_objects[next_activity as usize].contains(&otype);
 
             // Add to DFG if not divergent
             if !should_skip {
-                let edge = (current_activity.clone(), next_activity.clone());
-                *dfg.entry(edge).or_insert(0) += 1;
+                let (edge, per_type) = dfg.entry((current_activity, next_activity)).or_default();
+                let type_edge = per_type.entry(otype).or_default();
+                edge.count += 1;
+                type_edge.count += 1;
+                if object_edges.insert((current_activity, next_activity)) {
+                    edge.objects += 1;
+                    type_edge.objects += 1;
+                }
+                waiting_times
+                    .entry((current_activity, next_activity, otype))
+                    .or_default()
+                    .push(waiting_seconds(pair[0], pair[1]));
             }
         }
     }
 
     // Iterate over dfg to compute total number of edges
-    let total_edges: usize = dfg.values().copied().sum();
+    let total_edges: usize = dfg.values().map(|(edge, _)| edge.count).sum();
 
-    // Convert counts to rounded percentage values
+    // Rounded percentage values, next to the raw counts
     if total_edges > 0 {
-        for value in dfg.values_mut() {
-            let pct = ((*value as f64) / (total_edges as f64) * 100.0).ceil() as usize;
-            *value = pct;
+        for (edge, _) in dfg.values_mut() {
+            edge.percentage = ((edge.count as f64) / (total_edges as f64) * 100.0).ceil() as usize;
         }
     }
 
-    (dfg, start_activities, end_activities)
-}
\ No newline at end of file
+    let edges = dfg.into_iter()
+        .map(|((a, b), (weights, per_type))| {
+            let mut edge_waiting_times = Vec::new();
+            let object_types = per_type
+                .into_iter()
+                .map(|(otype, mut type_edge)| {
+                    let durations = waiting_times.remove(&(a, b, otype)).unwrap_or_default();
+                    edge_waiting_times.extend_from_slice(&durations);
+                    type_edge.waiting_time = WaitingTimeStats::from_values(durations);
+                    (index.object_type(otype).to_string(), type_edge)
+                })
+                .collect();
+            let edge = OCDfgEdge {
+                weights,
+                waiting_time: WaitingTimeStats::from_values(edge_waiting_times),
+                object_types,
+            };
+            ((index.activity(a).to_string(), index.activity(b).to_string()), edge)
+        })
+        .collect();
+    let per_type = |activities: HashSet<(ObjectTypeId, ActivityId)>| {
+        let mut map: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (otype, activity) in activities {
+            map.entry(index.object_type(otype).to_string()).or_default().insert(index.activity(activity).to_string());
+        }
+        map
+    };
+
+    OCDfg {
+        edges,
+        start_activities: per_type(start_activities),
+        end_activities: per_type(end_activities),
+    }
+}
+
+/// Seconds from the event of `from` to the event of `to`
+pub fn waiting_seconds(from: &Relation, to: &Relation) -> f64 {
+    (to.time - from.time).num_milliseconds() as f64 / 1000.0
+}
+
+/// Projects the DFG onto one weight, giving the plain DFG the cut search works on.
+pub fn weighted_dfg(
+    dfg: &HashMap<(String, String), DfgEdge>,
+    weight: EdgeWeight,
+) -> HashMap<(String, String), usize> {
+    dfg.iter()
+        .map(|(key, edge)| (key.clone(), weight.of(edge)))
+        .collect()
+}
+
+/// Brings the costs of adding edges, which are on the 0-100 scale of [`EdgeWeight::Percentage`],
+/// to `weight`: a cost of `c` becomes `c` percent of the weight summed over all edges of `dfg`.
+pub fn scale_costs_to_weight(
+    costs: &mut HashMap<(String, String), f64>,
+    dfg: &HashMap<(String, String), DfgEdge>,
+    weight: EdgeWeight,
+) {
+    if weight == EdgeWeight::Percentage {
+        return;
+    }
+    let total: usize = dfg.values().map(|edge| weight.of(edge)).sum();
+    for cost in costs.values_mut() {
+        *cost *= total as f64 / 100.0;
+    }
+}
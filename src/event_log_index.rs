@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::ocel_stream::{CompactOCEL, UNDECLARED};
+use crate::types::{OCELEvent, OCELObject};
+
+pub type EventId = u32;
+pub type ActivityId = u32;
+pub type ObjectId = u32;
+pub type ObjectTypeId = u32;
+
+/// One E2O link: event `event` (of activity `activity`) touches object `object` (of type `object_type`).
+///
+/// All names are interned in the owning [`EventLogIndex`], so a relation is a few integers and a timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Relation {
+    pub event: EventId,
+    pub activity: ActivityId,
+    pub time: DateTime<FixedOffset>,
+    pub object: ObjectId,
+    pub object_type: ObjectTypeId,
+}
+
+/// How events with the same timestamp are ordered
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Lexicographically by event id
+    #[default]
+    EventId,
+    /// In the order the events appear in the log file
+    FileOrder,
+    /// By position in the given activity list; unlisted activities go last, then file order
+    ActivityOrder(Vec<String>),
+}
+
+impl TieBreak {
+    /// Parses the `tie_break` query parameter (`event_id`, `file_order` or `activity_order`);
+    /// `activity_order` needs the comma separated activity list as well.
+    pub fn from_params(tie_break: Option<&str>, activity_order: Option<&str>) -> Result<Self, String> {
+        match tie_break.unwrap_or("event_id") {
+            "event_id" => Ok(TieBreak::EventId),
+            "file_order" => Ok(TieBreak::FileOrder),
+            "activity_order" => {
+                let order: Vec<String> = activity_order
+                    .unwrap_or("")
+                    .split(',')
+                    .map(|activity| activity.trim().to_string())
+                    .filter(|activity| !activity.is_empty())
+                    .collect();
+                if order.is_empty() {
+                    return Err("tie_break=activity_order needs a comma separated activity_order list".to_string());
+                }
+                Ok(TieBreak::ActivityOrder(order))
+            }
+            other => Err(format!(
+                "Unknown tie_break '{}', expected event_id, file_order or activity_order",
+                other
+            )),
+        }
+    }
+}
+
+/// Relation table of a log with interned event ids, activities, object ids and object types.
+///
+/// Replaces the `(eid, activity, timestamp, oid, otype)` string tuples. Relations are sorted by
+/// the instant they happened (independent of the UTC offset), equal instants by the [`TieBreak`]
+/// policy, so every per-object trace is already in time order.
+#[derive(Debug, Default, Clone)]
+pub struct EventLogIndex {
+    pub event_ids: Vec<String>,
+    pub activities: Vec<String>,
+    pub object_ids: Vec<String>,
+    pub object_types: Vec<String>,
+    pub relations: Vec<Relation>,
+    activity_index: HashMap<String, ActivityId>,
+    object_type_index: HashMap<String, ObjectTypeId>,
+}
+
+fn intern(names: &mut Vec<String>, index: &mut HashMap<String, u32>, name: &str) -> u32 {
+    if let Some(&i) = index.get(name) {
+        return i;
+    }
+    let i = names.len() as u32;
+    names.push(name.to_string());
+    index.insert(name.to_string(), i);
+    i
+}
+
+impl EventLogIndex {
+    /// Builds the relation table; relationships to undeclared objects are skipped.
+    pub fn from_ocel(events: &[OCELEvent], objects: &[OCELObject], tie_break: &TieBreak) -> Self {
+        let mut index = EventLogIndex::default();
+
+        let mut object_lookup: HashMap<&str, (ObjectId, ObjectTypeId)> = HashMap::new();
+        for object in objects {
+            let object_type = intern(&mut index.object_types, &mut index.object_type_index, &object.object_type);
+            let object_id = index.object_ids.len() as ObjectId;
+            index.object_ids.push(object.id.clone());
+            // Later duplicates win, as in a plain id -> object map
+            object_lookup.insert(object.id.as_str(), (object_id, object_type));
+        }
+
+        let mut dangling = 0;
+        for event in events {
+            let event_id = index.event_ids.len() as EventId;
+            index.event_ids.push(event.id.clone());
+            let activity = intern(&mut index.activities, &mut index.activity_index, &event.event_type);
+
+            for relationship in &event.relationships {
+                if let Some(&(object, object_type)) = object_lookup.get(relationship.object_id.as_str()) {
+                    index.relations.push(Relation { event: event_id, activity, time: event.time, object, object_type });
+                } else {
+                    dangling += 1;
+                }
+            }
+        }
+
+        if dangling > 0 {
+            println!("Skipped {} relationship(s) to undeclared objects", dangling);
+        }
+
+        index.sort_relations(tie_break);
+        index
+    }
+
+    /// Builds the relation table from a [`CompactOCEL`], reusing its interned names.
+    pub fn from_compact(compact: CompactOCEL, tie_break: &TieBreak) -> Self {
+        let mut index = EventLogIndex {
+            activity_index: compact.event_types.iter().enumerate().map(|(i, name)| (name.clone(), i as u32)).collect(),
+            object_type_index: compact.object_types.iter().enumerate().map(|(i, name)| (name.clone(), i as u32)).collect(),
+            activities: compact.event_types,
+            object_types: compact.object_types,
+            object_ids: compact.objects.iter().map(|object| object.id.to_string()).collect(),
+            ..Default::default()
+        };
+
+        for event in compact.events {
+            let event_id = index.event_ids.len() as EventId;
+            index.event_ids.push(event.id.into_string());
+            for (object, _) in event.relationships {
+                let object_type = compact.objects[object as usize].object_type;
+                if object_type == UNDECLARED {
+                    continue;
+                }
+                index.relations.push(Relation { event: event_id, activity: event.event_type, time: event.time, object, object_type });
+            }
+        }
+
+        index.sort_relations(tie_break);
+        index
+    }
+
+    // Event ids are assigned in file order, so `event` doubles as the file position. The sort
+    // is stable, which keeps the relationships of one event in their declared order.
+    fn sort_relations(&mut self, tie_break: &TieBreak) {
+        let event_ids = &self.event_ids;
+        match tie_break {
+            TieBreak::EventId => self.relations.sort_by(|a, b| {
+                a.time
+                    .cmp(&b.time)
+                    .then_with(|| event_ids[a.event as usize].cmp(&event_ids[b.event as usize]))
+                    .then(a.event.cmp(&b.event))
+            }),
+            TieBreak::FileOrder => self.relations.sort_by(|a, b| a.time.cmp(&b.time).then(a.event.cmp(&b.event))),
+            TieBreak::ActivityOrder(order) => {
+                let rank: Vec<usize> = self
+                    .activities
+                    .iter()
+                    .map(|activity| order.iter().position(|a| a == activity).unwrap_or(order.len()))
+                    .collect();
+                self.relations.sort_by(|a, b| {
+                    a.time
+                        .cmp(&b.time)
+                        .then(rank[a.activity as usize].cmp(&rank[b.activity as usize]))
+                        .then(a.event.cmp(&b.event))
+                })
+            }
+        }
+    }
+
+    pub fn event_id(&self, event: EventId) -> &str {
+        &self.event_ids[event as usize]
+    }
+
+    pub fn activity(&self, activity: ActivityId) -> &str {
+        &self.activities[activity as usize]
+    }
+
+    pub fn object_id(&self, object: ObjectId) -> &str {
+        &self.object_ids[object as usize]
+    }
+
+    pub fn object_type(&self, object_type: ObjectTypeId) -> &str {
+        &self.object_types[object_type as usize]
+    }
+
+    pub fn activity_id(&self, name: &str) -> Option<ActivityId> {
+        self.activity_index.get(name).copied()
+    }
+
+    pub fn object_type_id(&self, name: &str) -> Option<ObjectTypeId> {
+        self.object_type_index.get(name).copied()
+    }
+
+    /// Relations grouped per object, in time order; objects without relations are left out.
+    pub fn relations_by_object(&self) -> Vec<Vec<&Relation>> {
+        let mut groups: Vec<Vec<&Relation>> = vec![Vec::new(); self.object_ids.len()];
+        for relation in &self.relations {
+            groups[relation.object as usize].push(relation);
+        }
+        groups.retain(|group| !group.is_empty());
+        groups
+    }
+
+    /// Turns a map keyed by activity name (such as the interaction patterns) into per-activity
+    /// object type ids; names unknown to this log are dropped.
+    pub fn object_types_per_activity(&self, map: &HashMap<String, Vec<String>>) -> Vec<Vec<ObjectTypeId>> {
+        let mut per_activity = vec![Vec::new(); self.activities.len()];
+        for (activity, object_types) in map {
+            if let Some(activity) = self.activity_id(activity) {
+                per_activity[activity as usize] = object_types.iter().filter_map(|name| self.object_type_id(name)).collect();
+            }
+        }
+        per_activity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OCELRelationship;
+
+    // Events at the same instant on one object, listed in the file as e3 (c), e1 (a), e2 (b);
+    // e3 is written with another UTC offset
+    fn activities_in_order(tie_break: TieBreak) -> Vec<String> {
+        let event = |id: &str, activity: &str, time: &str| OCELEvent {
+            id: id.to_string(),
+            event_type: activity.to_string(),
+            time: DateTime::parse_from_rfc3339(time).unwrap(),
+            attributes: Vec::new(),
+            relationships: vec![OCELRelationship { object_id: "o1".to_string(), qualifier: String::new() }],
+        };
+        let events = [
+            event("e3", "c", "2024-01-01T10:00:00+02:00"),
+            event("e1", "a", "2024-01-01T08:00:00+00:00"),
+            event("e2", "b", "2024-01-01T08:00:00+00:00"),
+        ];
+        let objects = [OCELObject {
+            id: "o1".to_string(),
+            object_type: "order".to_string(),
+            attributes: Vec::new(),
+            relationships: Vec::new(),
+        }];
+
+        let index = EventLogIndex::from_ocel(&events, &objects, &tie_break);
+        index.relations.iter().map(|relation| index.activity(relation.activity).to_string()).collect()
+    }
+
+    #[test]
+    fn equal_instants_follow_the_tie_break() {
+        assert_eq!(activities_in_order(TieBreak::EventId), ["a", "b", "c"]);
+        assert_eq!(activities_in_order(TieBreak::FileOrder), ["c", "a", "b"]);
+        // Unlisted activities go last, in file order
+        assert_eq!(activities_in_order(TieBreak::ActivityOrder(vec!["b".to_string()])), ["b", "c", "a"]);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::types::ObjectTrace;
+
+/// Activities one object performs within the activity set being cut, in time order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubTrace {
+    pub object_type: String,
+    pub activities: Vec<String>,
+}
+
+/// Restricts the object traces of a log to `activities`, giving the sublog discovery starts with
+pub fn sub_traces(traces: &[ObjectTrace], activities: &HashSet<String>) -> Vec<SubTrace> {
+    let traces = traces
+        .iter()
+        .map(|trace| SubTrace {
+            object_type: trace.object_type.clone(),
+            activities: trace.activities.clone(),
+        })
+        .collect::<Vec<_>>();
+    project(&traces, activities)
+}
+
+// Keeps the activities of every trace that are in `activities`
+fn project(traces: &[SubTrace], activities: &HashSet<String>) -> Vec<SubTrace> {
+    let projected = traces
+        .iter()
+        .map(|trace| SubTrace {
+            object_type: trace.object_type.clone(),
+            activities: trace.activities.iter().filter(|a| activities.contains(*a)).cloned().collect(),
+        })
+        .collect();
+    drop_unrelated(projected)
+}
+
+// Drops the traces of object types without any activity in the sublog: the activities being
+// cut are unrelated to those objects rather than skipped by them
+fn drop_unrelated(traces: Vec<SubTrace>) -> Vec<SubTrace> {
+    let related: HashSet<String> = traces
+        .iter()
+        .filter(|trace| !trace.activities.is_empty())
+        .map(|trace| trace.object_type.clone())
+        .collect();
+    traces.into_iter().filter(|trace| related.contains(&trace.object_type)).collect()
+}
+
+/// Splits a sublog over the branches of a cut as the Inductive Miner does. An exclusive
+/// choice sends every trace to the branch it has most activities of, a redo cut splits traces
+/// into segments within one branch each, and sequence and parallel cuts project every trace
+/// onto every branch.
+pub fn split(cut_type: &str, traces: &[SubTrace], sets: &[HashSet<String>]) -> Vec<Vec<SubTrace>> {
+    let branch_of = |activity: &String| sets.iter().position(|set| set.contains(activity));
+    match cut_type {
+        "exclusive" => {
+            let mut branches = vec![Vec::new(); sets.len()];
+            for trace in traces {
+                let mut counts = vec![0; sets.len()];
+                for branch in trace.activities.iter().filter_map(branch_of) {
+                    counts[branch] += 1;
+                }
+                // Ties go to the first branch
+                let Some((branch, _)) = counts
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, count)| **count > 0)
+                    .min_by_key(|(_, count)| std::cmp::Reverse(**count))
+                else {
+                    continue;
+                };
+                branches[branch].push(trace.clone());
+            }
+            branches.iter().zip(sets).map(|(branch, set)| project(branch, set)).collect()
+        }
+        "redo" => {
+            let mut branches = vec![Vec::new(); sets.len()];
+            for trace in traces {
+                let mut segment: Vec<String> = Vec::new();
+                let mut segment_branch = 0;
+                for activity in &trace.activities {
+                    let Some(branch) = branch_of(activity) else {
+                        continue;
+                    };
+                    if branch != segment_branch && !segment.is_empty() {
+                        branches[segment_branch].push(SubTrace { object_type: trace.object_type.clone(), activities: std::mem::take(&mut segment) });
+                    }
+                    segment_branch = branch;
+                    segment.push(activity.clone());
+                }
+                if !segment.is_empty() {
+                    branches[segment_branch].push(SubTrace { object_type: trace.object_type.clone(), activities: segment });
+                }
+            }
+            branches.into_iter().map(drop_unrelated).collect()
+        }
+        _ => sets.iter().map(|set| project(traces, set)).collect(),
+    }
+}
+
+/// Whether some object of a type related to the sublog skips all of its activities
+pub fn has_empty_traces(traces: &[SubTrace]) -> bool {
+    traces.iter().any(|trace| trace.activities.is_empty())
+}
+
+pub fn without_empty_traces(traces: &[SubTrace]) -> Vec<SubTrace> {
+    traces.iter().filter(|trace| !trace.activities.is_empty()).cloned().collect()
+}
+
+/// First of `activities` that every trace of the object types performing it contains exactly
+/// once
+pub fn activity_once_per_trace(traces: &[SubTrace], activities: &[String]) -> Option<String> {
+    activities
+        .iter()
+        .find(|activity| {
+            let types: HashSet<&str> = traces
+                .iter()
+                .filter(|trace| trace.activities.contains(activity))
+                .map(|trace| trace.object_type.as_str())
+                .collect();
+            !types.is_empty()
+                && traces
+                    .iter()
+                    .filter(|trace| types.contains(trace.object_type.as_str()))
+                    .all(|trace| trace.activities.iter().filter(|a| a == activity).count() == 1)
+        })
+        .cloned()
+}
+
+/// Sublogs of an activity taken out of the others: the traces projected onto the activity
+/// and onto the rest
+pub fn split_off_activity(traces: &[SubTrace], activity: &str, rest: &HashSet<String>) -> (Vec<SubTrace>, Vec<SubTrace>) {
+    let single: HashSet<String> = HashSet::from([activity.to_string()]);
+    (project(traces, &single), project(traces, rest))
+}
+
+/// Cuts every trace between two consecutive activities `splits_between` holds for, as the
+/// tau loop fall-throughs do
+pub fn split_traces_between(traces: &[SubTrace], splits_between: impl Fn(&str, &str) -> bool) -> Vec<SubTrace> {
+    let mut pieces = Vec::new();
+    for trace in traces {
+        let mut piece: Vec<String> = Vec::new();
+        for activity in &trace.activities {
+            if piece.last().is_some_and(|previous| splits_between(previous, activity)) {
+                pieces.push(SubTrace { object_type: trace.object_type.clone(), activities: std::mem::take(&mut piece) });
+            }
+            piece.push(activity.clone());
+        }
+        pieces.push(SubTrace { object_type: trace.object_type.clone(), activities: piece });
+    }
+    pieces
+}
+
+/// The DFG with `activity` left out: its predecessors are connected to its successors, as
+/// they would directly follow each other in traces without the activity
+pub fn dfg_without_activity(dfg: &HashMap<(String, String), usize>, activity: &str) -> HashMap<(String, String), usize> {
+    let mut reduced: HashMap<(String, String), usize> = HashMap::new();
+    let mut incoming: Vec<(&String, usize)> = Vec::new();
+    let mut outgoing: Vec<(&String, usize)> = Vec::new();
+    for ((from, to), weight) in dfg {
+        if from == activity && to == activity {
+            continue;
+        } else if to == activity {
+            incoming.push((from, *weight));
+        } else if from == activity {
+            outgoing.push((to, *weight));
+        } else {
+            *reduced.entry((from.clone(), to.clone())).or_insert(0) += weight;
+        }
+    }
+    for (from, in_weight) in &incoming {
+        for (to, out_weight) in &outgoing {
+            *reduced.entry(((*from).clone(), (*to).clone())).or_insert(0) += (*in_weight).min(*out_weight);
+        }
+    }
+    reduced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(object_type: &str, activities: &[&str]) -> SubTrace {
+        SubTrace {
+            object_type: object_type.to_string(),
+            activities: activities.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    fn set(activities: &[&str]) -> HashSet<String> {
+        activities.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn split_follows_the_cut_type() {
+        let log = vec![trace("order", &["a", "b", "c"]), trace("order", &["c"]), trace("item", &["a"])];
+        let sets = [set(&["a", "b"]), set(&["c"])];
+
+        // Each trace goes to the branch it has most activities of
+        let exclusive = split("exclusive", &log, &sets);
+        assert_eq!(exclusive[0], vec![trace("order", &["a", "b"]), trace("item", &["a"])]);
+        assert_eq!(exclusive[1], vec![trace("order", &["c"])]);
+
+        // Every trace on every branch; items never do c and leave that branch
+        let sequence = split("sequence", &log, &sets);
+        assert_eq!(sequence[0], vec![trace("order", &["a", "b"]), trace("order", &[]), trace("item", &["a"])]);
+        assert_eq!(sequence[1], vec![trace("order", &["c"]), trace("order", &["c"])]);
+
+        // Segments within one branch each
+        let redo = split("redo", &[trace("order", &["a", "c", "b", "a"])], &sets);
+        assert_eq!(redo[0], vec![trace("order", &["a"]), trace("order", &["b", "a"])]);
+        assert_eq!(redo[1], vec![trace("order", &["c"])]);
+    }
+
+    #[test]
+    fn traces_are_cut_between_loop_edges() {
+        let pieces = split_traces_between(&[trace("order", &["a", "b", "a", "b"]), trace("order", &["a"])], |from, to| {
+            from == "b" && to == "a"
+        });
+        assert_eq!(pieces, vec![trace("order", &["a", "b"]), trace("order", &["a", "b"]), trace("order", &["a"])]);
+    }
+
+    #[test]
+    fn dfg_without_activity_bridges_over_it() {
+        let dfg: HashMap<(String, String), usize> = [("a", "b", 3), ("b", "c", 2), ("b", "b", 1), ("a", "c", 1)]
+            .iter()
+            .map(|(from, to, weight)| ((from.to_string(), to.to_string()), *weight))
+            .collect();
+        let reduced = dfg_without_activity(&dfg, "b");
+        assert_eq!(reduced, HashMap::from([(("a".to_string(), "c".to_string()), 3)]));
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use process_mining::core::event_data::case_centric::xes::{import_xes_path, XESImportOptions};
+use process_mining::core::event_data::case_centric::AttributeValue;
+use serde::Deserialize;
+
+use crate::legacy_ocel_import::{upgrade_to_ocel2, LegacyEvent, LegacyLog, LegacyObject};
+use crate::types::{parse_timestamp, OCELAttributeValue, OCEL};
+
+/// Tells the importer how to read a flat event table (CSV or XES)
+#[derive(Debug, Clone, Deserialize)]
+pub struct FlatLogMapping {
+    /// Column holding the activity name
+    pub activity_column: String,
+    /// Column holding the event timestamp
+    pub timestamp_column: String,
+    /// Column holding the event id. Rows sharing an id are merged into one event and must
+    /// agree on its activity, timestamp and attribute values; without it every row becomes
+    /// its own event.
+    #[serde(default)]
+    pub event_id_column: Option<String>,
+    /// Columns holding object ids, with the object type of each column
+    pub object_columns: Vec<ObjectColumn>,
+    /// Splits cells that hold several object ids, e.g. `"i1;i2"`
+    #[serde(default)]
+    pub id_separator: Option<String>,
+    /// CSV field delimiter, `,` by default
+    #[serde(default)]
+    pub delimiter: Option<char>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ObjectColumn {
+    pub column: String,
+    pub object_type: String,
+}
+
+/// One row of the flat table: column name and value, in column order
+type FlatRow = Vec<(String, FlatValue)>;
+
+/// Cell of a flat table. CSV cells stay raw text until they are known to be attributes, so
+/// that ids like `007` or `1.50` are not rewritten by type inference; XES values come typed.
+#[derive(Debug, Clone)]
+enum FlatValue {
+    Raw(String),
+    Typed(OCELAttributeValue),
+}
+
+impl FlatValue {
+    fn to_attribute(&self) -> OCELAttributeValue {
+        match self {
+            FlatValue::Raw(cell) => infer_value(cell),
+            FlatValue::Typed(value) => value.clone(),
+        }
+    }
+}
+
+pub fn is_flat_log_file(file_name: &str) -> bool {
+    let lower = file_name.to_lowercase();
+    lower.ends_with(".csv") || lower.ends_with(".xes") || lower.ends_with(".xes.gz")
+}
+
+/// Reads a CSV or XES file and builds an [`OCEL`] with E2O relationships from the mapping.
+pub fn import_flat_log(path: &Path, mapping: &FlatLogMapping) -> Result<OCEL, String> {
+    let lower = path.to_string_lossy().to_lowercase();
+    let rows = if lower.ends_with(".csv") {
+        read_csv_rows(path, mapping)?
+    } else if lower.ends_with(".xes") || lower.ends_with(".xes.gz") {
+        read_xes_rows(path)?
+    } else {
+        return Err(format!("Unsupported flat log file: {}", path.display()));
+    };
+
+    println!("Read {} rows from {}", rows.len(), path.display());
+    build_ocel_from_rows(rows, mapping)
+}
+
+// CSV attribute cells are untyped; numbers, booleans and timestamps are recognized so
+// that the upgraded log gets sensible attribute types
+fn infer_value(cell: &str) -> OCELAttributeValue {
+    if cell.is_empty() {
+        return OCELAttributeValue::Null;
+    }
+    if let Ok(i) = cell.parse::<i64>() {
+        return OCELAttributeValue::Integer(i);
+    }
+    if let Ok(f) = cell.parse::<f64>() {
+        return OCELAttributeValue::Float(f);
+    }
+    if let Ok(b) = cell.parse::<bool>() {
+        return OCELAttributeValue::Boolean(b);
+    }
+    if let Some(dt) = parse_timestamp(cell) {
+        return OCELAttributeValue::Time(dt);
+    }
+    OCELAttributeValue::String(cell.to_string())
+}
+
+fn read_csv_rows(path: &Path, mapping: &FlatLogMapping) -> Result<Vec<FlatRow>, String> {
+    let delimiter = mapping.delimiter.unwrap_or(',');
+    if !delimiter.is_ascii() {
+        return Err(format!("CSV delimiter must be an ASCII character, got '{}'", delimiter));
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Failed to read CSV header of {}: {}", path.display(), e))?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+
+    let missing: Vec<&str> = mapped_columns(mapping).filter(|column| !headers.iter().any(|h| h == column)).collect();
+    if !missing.is_empty() {
+        return Err(format!("Columns {:?} are not in the header of {}", missing, path.display()));
+    }
+
+    let mut rows = Vec::new();
+    for (line, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| format!("Failed to read CSV row {}: {}", line + 2, e))?;
+        let row: FlatRow = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(header, cell)| (header.clone(), FlatValue::Raw(cell.trim().to_string())))
+            .collect();
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+// Columns the mapping reads, which every row has to provide
+fn mapped_columns(mapping: &FlatLogMapping) -> impl Iterator<Item = &str> {
+    [mapping.activity_column.as_str(), mapping.timestamp_column.as_str()]
+        .into_iter()
+        .chain(mapping.event_id_column.as_deref())
+        .chain(mapping.object_columns.iter().map(|c| c.column.as_str()))
+}
+
+fn xes_to_attribute_value(value: &AttributeValue) -> OCELAttributeValue {
+    match value {
+        AttributeValue::String(s) => OCELAttributeValue::String(s.clone()),
+        AttributeValue::Date(dt) => OCELAttributeValue::Time(*dt),
+        AttributeValue::Int(i) => OCELAttributeValue::Integer(*i),
+        AttributeValue::Float(f) => OCELAttributeValue::Float(*f),
+        AttributeValue::Boolean(b) => OCELAttributeValue::Boolean(*b),
+        AttributeValue::ID(id) => OCELAttributeValue::String(id.to_string()),
+        // Nested attributes have no flat representation
+        AttributeValue::List(_) | AttributeValue::Container(_) | AttributeValue::None() => OCELAttributeValue::Null,
+    }
+}
+
+// Every event becomes one row; trace attributes are added with a `case:` prefix
+// (as pm4py does when flattening), so e.g. `case:concept:name` can be mapped to an object type
+fn read_xes_rows(path: &Path) -> Result<Vec<FlatRow>, String> {
+    let log = import_xes_path(path, XESImportOptions::default())
+        .map_err(|e| format!("Failed to parse XES {}: {:?}", path.display(), e))?;
+
+    let mut rows = Vec::new();
+    for trace in &log.traces {
+        let trace_values: FlatRow = trace
+            .attributes
+            .iter()
+            .map(|attr| (format!("case:{}", attr.key), FlatValue::Typed(xes_to_attribute_value(&attr.value))))
+            .collect();
+
+        for event in &trace.events {
+            let mut row: FlatRow = event
+                .attributes
+                .iter()
+                .map(|attr| (attr.key.clone(), FlatValue::Typed(xes_to_attribute_value(&attr.value))))
+                .collect();
+            row.extend(trace_values.iter().cloned());
+            rows.push(row);
+        }
+    }
+
+    Ok(rows)
+}
+
+// Text of an id or activity cell: CSV cells as written, typed XES values printed
+fn value_as_string(value: &FlatValue) -> Option<String> {
+    let value = match value {
+        FlatValue::Raw(cell) if cell.is_empty() => return None,
+        FlatValue::Raw(cell) => return Some(cell.clone()),
+        FlatValue::Typed(value) => value,
+    };
+    match value {
+        OCELAttributeValue::Null => None,
+        OCELAttributeValue::String(s) if s.is_empty() => None,
+        OCELAttributeValue::String(s) => Some(s.clone()),
+        OCELAttributeValue::Time(dt) => Some(dt.to_rfc3339()),
+        OCELAttributeValue::Integer(i) => Some(i.to_string()),
+        OCELAttributeValue::Float(f) => Some(f.to_string()),
+        OCELAttributeValue::Boolean(b) => Some(b.to_string()),
+    }
+}
+
+fn build_ocel_from_rows(rows: Vec<FlatRow>, mapping: &FlatLogMapping) -> Result<OCEL, String> {
+    let mut log = LegacyLog {
+        declared_object_types: mapping.object_columns.iter().map(|c| c.object_type.clone()).collect(),
+        ..Default::default()
+    };
+
+    let object_columns: HashSet<&str> = mapping.object_columns.iter().map(|c| c.column.as_str()).collect();
+
+    // event id -> index into log.events and the row it came from, so rows of the same event
+    // are merged
+    let mut event_index: HashMap<String, (usize, usize)> = HashMap::new();
+    // (object type, raw id) -> OCEL object id
+    let mut object_ids: HashMap<(String, String), String> = HashMap::new();
+    let mut object_type_of_id: HashMap<String, String> = HashMap::new();
+
+    for (row_number, row) in rows.into_iter().enumerate() {
+        let get = |column: &str| row.iter().find(|(name, _)| name == column).map(|(_, v)| v);
+
+        let activity = get(&mapping.activity_column)
+            .and_then(value_as_string)
+            .ok_or_else(|| format!("Row {} has no value in activity column '{}'", row_number + 1, mapping.activity_column))?;
+
+        let timestamp = match get(&mapping.timestamp_column) {
+            Some(FlatValue::Typed(OCELAttributeValue::Time(dt))) => *dt,
+            Some(value) => value_as_string(value)
+                .and_then(|s| parse_timestamp(&s))
+                .ok_or_else(|| format!("Row {} has an unparsable timestamp in column '{}'", row_number + 1, mapping.timestamp_column))?,
+            None => {
+                return Err(format!("Row {} has no value in timestamp column '{}'", row_number + 1, mapping.timestamp_column));
+            }
+        };
+
+        let event_id = match &mapping.event_id_column {
+            Some(column) => get(column)
+                .and_then(value_as_string)
+                .ok_or_else(|| format!("Row {} has no value in event id column '{}'", row_number + 1, column))?,
+            None => format!("e{}", row_number + 1),
+        };
+
+        let mut omap: Vec<(String, String)> = Vec::new();
+        for object_column in &mapping.object_columns {
+            let Some(cell) = get(&object_column.column).and_then(value_as_string) else {
+                continue;
+            };
+            let raw_ids: Vec<String> = match &mapping.id_separator {
+                Some(separator) => cell.split(separator.as_str()).map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+                None => vec![cell],
+            };
+
+            for raw_id in raw_ids {
+                let key = (object_column.object_type.clone(), raw_id.clone());
+                let object_id = match object_ids.get(&key) {
+                    Some(object_id) => object_id.clone(),
+                    None => {
+                        // Flat tables often reuse ids across types ("1" for an order and an item);
+                        // OCEL object ids must be unique, so prefix the type on a clash
+                        let object_id = if object_type_of_id.contains_key(&raw_id) {
+                            format!("{}:{}", object_column.object_type, raw_id)
+                        } else {
+                            raw_id.clone()
+                        };
+                        object_type_of_id.insert(object_id.clone(), object_column.object_type.clone());
+                        object_ids.insert(key, object_id.clone());
+                        log.objects.push(LegacyObject {
+                            id: object_id.clone(),
+                            object_type: object_column.object_type.clone(),
+                            ovmap: Vec::new(),
+                        });
+                        object_id
+                    }
+                };
+                // The column name doubles as the E2O qualifier
+                omap.push((object_id, object_column.column.clone()));
+            }
+        }
+
+        let vmap: Vec<(String, OCELAttributeValue)> = row
+            .iter()
+            .filter(|(name, _)| {
+                *name != mapping.activity_column
+                    && *name != mapping.timestamp_column
+                    && Some(name) != mapping.event_id_column.as_ref()
+                    && !object_columns.contains(name.as_str())
+            })
+            .map(|(name, value)| (name.clone(), value.to_attribute()))
+            .filter(|(_, value)| *value != OCELAttributeValue::Null)
+            .collect();
+
+        match event_index.get(&event_id) {
+            Some(&(index, first_row)) => {
+                // Further rows of an event may add objects and attributes, but not contradict it
+                let event = &mut log.events[index];
+                let conflict = |what: &str, first: &dyn std::fmt::Debug, other: &dyn std::fmt::Debug| {
+                    format!(
+                        "Rows {} and {} share event id '{}' but differ in {}: {:?} and {:?}",
+                        first_row + 1,
+                        row_number + 1,
+                        event_id,
+                        what,
+                        first,
+                        other
+                    )
+                };
+                if event.activity != activity {
+                    return Err(conflict("activity", &event.activity, &activity));
+                }
+                if event.timestamp != timestamp {
+                    return Err(conflict("timestamp", &event.timestamp.to_rfc3339(), &timestamp.to_rfc3339()));
+                }
+                for (name, value) in vmap {
+                    match event.vmap.iter().find(|(known, _)| *known == name) {
+                        Some((_, known)) if *known != value => {
+                            return Err(conflict(&format!("attribute '{}'", name), known, &value));
+                        }
+                        Some(_) => {}
+                        None => event.vmap.push((name, value)),
+                    }
+                }
+                event.omap.extend(omap);
+            }
+            None => {
+                event_index.insert(event_id.clone(), (log.events.len(), row_number));
+                log.events.push(LegacyEvent { id: event_id, activity, timestamp, omap, vmap });
+            }
+        }
+    }
+
+    Ok(upgrade_to_ocel2(log))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(event_id_column: Option<&str>) -> FlatLogMapping {
+        FlatLogMapping {
+            activity_column: "activity".to_string(),
+            timestamp_column: "time".to_string(),
+            event_id_column: event_id_column.map(str::to_string),
+            object_columns: vec![
+                ObjectColumn { column: "order".to_string(), object_type: "order".to_string() },
+                ObjectColumn { column: "item".to_string(), object_type: "item".to_string() },
+            ],
+            id_separator: None,
+            delimiter: None,
+        }
+    }
+
+    fn row(cells: &[(&str, &str)]) -> FlatRow {
+        cells.iter().map(|(column, cell)| (column.to_string(), FlatValue::Raw(cell.to_string()))).collect()
+    }
+
+    // Writes `content` to a CSV file of its own in the temp directory and imports it
+    fn import_csv(name: &str, content: &str, mapping: &FlatLogMapping) -> Result<OCEL, String> {
+        let path = std::env::temp_dir().join(format!("flat-{}-{}.csv", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        let ocel = import_flat_log(&path, mapping);
+        std::fs::remove_file(&path).unwrap();
+        ocel
+    }
+
+    #[test]
+    fn ids_shared_by_two_types_get_the_type_prefixed() {
+        let csv = "activity,time,order,item\nplace,2024-01-01T10:00:00Z,1,1\npack,2024-01-02T10:00:00Z,1,007\n";
+        let ocel = import_csv("clash", csv, &mapping(None)).unwrap();
+        let objects: Vec<(&str, &str)> = ocel.objects.iter().map(|o| (o.id.as_str(), o.object_type.as_str())).collect();
+        assert_eq!(objects, vec![("1", "order"), ("item:1", "item"), ("007", "item")]);
+        assert_eq!(ocel.events[1].relationships[1].object_id, "007");
+    }
+
+    #[test]
+    fn missing_columns_are_reported() {
+        let error = import_csv("missing", "activity,timestamp,order\n", &mapping(None)).unwrap_err();
+        assert!(error.starts_with("Columns [\"time\", \"item\"] are not in the header"), "{}", error);
+
+        let csv = "activity,time,order,item\n,2024-01-01T10:00:00Z,o1,i1\n";
+        let error = import_csv("empty", csv, &mapping(None)).unwrap_err();
+        assert_eq!(error, "Row 1 has no value in activity column 'activity'");
+    }
+
+    #[test]
+    fn rows_of_one_event_must_agree() {
+        let first = row(&[("id", "e1"), ("activity", "pack"), ("time", "2024-01-01T10:00:00Z"), ("order", "o1"), ("weight", "3")]);
+        let more = row(&[("id", "e1"), ("activity", "pack"), ("time", "2024-01-01T10:00:00Z"), ("item", "i1"), ("weight", "3"), ("note", "x")]);
+        let ocel = build_ocel_from_rows(vec![first.clone(), more], &mapping(Some("id"))).unwrap();
+        assert_eq!(ocel.events.len(), 1);
+        assert_eq!(ocel.events[0].relationships.len(), 2);
+        assert_eq!(ocel.events[0].attributes.len(), 2);
+
+        let other_activity = row(&[("id", "e1"), ("activity", "ship"), ("time", "2024-01-01T10:00:00Z")]);
+        let error = build_ocel_from_rows(vec![first.clone(), other_activity], &mapping(Some("id"))).unwrap_err();
+        assert!(error.starts_with("Rows 1 and 2 share event id 'e1' but differ in activity"), "{}", error);
+
+        let other_weight = row(&[("id", "e1"), ("activity", "pack"), ("time", "2024-01-01T10:00:00Z"), ("weight", "4")]);
+        let error = build_ocel_from_rows(vec![first, other_weight], &mapping(Some("id"))).unwrap_err();
+        assert!(error.contains("differ in attribute 'weight'"), "{}", error);
+    }
+}
@@ -1,14 +1,57 @@
-use crate::types::{CutSuggestionsList, Edge, Graph, Node, ProcessForest};
+use crate::types::{CutSuggestionsList, Edge, Graph, Node, OCDfg, ProcessForest, WaitingTimeStats};
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 use serde::de::DeserializeOwned;
+use itertools::Itertools;
 
 pub fn dfg_to_json(dfg: &HashMap<(String, String), usize>) -> serde_json::Value {
+    serde_json::to_value(dfg_to_graph(dfg)).unwrap()
+}
+
+// Same as dfg_to_json, additionally reporting the raw count, object count, percentage,
+// waiting time and object type breakdown of every edge found in `oc_dfg`, and its start and
+// end activities per object type
+pub fn dfg_to_json_with_oc_dfg(
+    dfg: &HashMap<(String, String), usize>,
+    oc_dfg: &OCDfg,
+) -> serde_json::Value {
+    let mut graph = dfg_to_graph(dfg);
+    for edge in &mut graph.edges {
+        let Some(oc_edge) = oc_dfg.edges.get(&(edge.source.clone(), edge.target.clone())) else {
+            continue;
+        };
+        edge.count = Some(oc_edge.weights.count);
+        edge.objects = Some(oc_edge.weights.objects);
+        edge.percentage = Some(oc_edge.weights.percentage);
+        edge.waiting_time = oc_edge.waiting_time;
+        edge.object_types = Some(oc_edge.object_types.clone());
+    }
+    graph.start_activities = oc_dfg.start_activities.clone();
+    graph.end_activities = oc_dfg.end_activities.clone();
+    serde_json::to_value(graph).unwrap()
+}
+
+// Same as dfg_to_json, additionally reporting the waiting time of every edge found in
+// `waiting_times`
+pub fn dfg_to_json_with_waiting_times(
+    dfg: &HashMap<(String, String), usize>,
+    waiting_times: &HashMap<(String, String), WaitingTimeStats>,
+) -> serde_json::Value {
+    let mut graph = dfg_to_graph(dfg);
+    for edge in &mut graph.edges {
+        edge.waiting_time = waiting_times.get(&(edge.source.clone(), edge.target.clone())).copied();
+    }
+    serde_json::to_value(graph).unwrap()
+}
+
+// Nodes and edges of a DFG, with the edge weight as cost and no further edge details. Edges
+// are listed in order of their activities, nodes in order of first appearance among them.
+fn dfg_to_graph(dfg: &HashMap<(String, String), usize>) -> Graph {
     let mut seen_nodes: HashSet<String> = HashSet::new();
     let mut nodes: Vec<Node> = Vec::new();
     let mut edges: Vec<Edge> = Vec::new();
 
-    for ((source, target), cost) in dfg.iter() {
+    for ((source, target), cost) in dfg.iter().sorted() {
         if seen_nodes.insert(source.clone()) {
             nodes.push(Node {
                 id: source.clone(),
@@ -28,12 +71,21 @@ pub fn dfg_to_json(dfg: &HashMap<(String, String), usize>) -> serde_json::Value
             source: source.clone(),
             target: target.clone(),
             label: format!("Edge {}-{}", source, target),
-            cost: *cost
+            cost: *cost,
+            count: None,
+            objects: None,
+            percentage: None,
+            waiting_time: None,
+            object_types: None,
         });
     }
 
-    let graph = Graph { nodes, edges };
-    serde_json::to_value(graph).unwrap()
+    Graph {
+        nodes,
+        edges,
+        start_activities: Default::default(),
+        end_activities: Default::default(),
+    }
 }
 
 pub fn cost_to_add_edges_to_json(dfg: &HashMap<(String, String), f64>) -> serde_json::Value {
@@ -1,104 +1,91 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::divergence_free_dfg::waiting_seconds;
+use crate::event_log_index::{ActivityId, EventId, EventLogIndex};
+use crate::types::WaitingTimeStats;
+
+/// DFG of the traces of one object type
+#[derive(Debug, Clone, Default)]
+pub struct ObjectTypeDfg {
+    pub dfg: HashMap<(String, String), usize>,
+    pub start_activities: HashSet<String>,
+    pub end_activities: HashSet<String>,
+    /// Waiting time between the two activities of every edge
+    pub waiting_times: HashMap<(String, String), WaitingTimeStats>,
+}
+
 pub fn get_dfg_by_object_type(
-    relations: &Vec<(String, String, String, String, String)>,
+    index: &EventLogIndex,
     divergent_objects: &HashMap<String, Vec<String>>,
-) -> HashMap<String, (HashMap<(String, String), usize>, HashSet<String>, HashSet<String>)> {
+) -> HashMap<String, ObjectTypeDfg> {
 
-    // Response Format: HashMap<ObjectType, (DFG, StartActivities, EndActivities)>
+    // Response Format: HashMap<ObjectType, ObjectTypeDfg>
     
-    let mut dfg_sets: HashMap<String, (HashMap<(String, String), usize>, HashSet<String>, HashSet<String>)> = HashMap::new();
+    let divergent_objects = index.object_types_per_activity(divergent_objects);
 
-    // Group relations by object type (otype at index 4)
-    let mut grouped_by_otype: HashMap<String, Vec<&(String, String, String, String, String)>> = HashMap::new();
-    
-    for relation in relations {
-        grouped_by_otype
-            .entry(relation.4.clone()) // otype is at index 4
-            .or_insert_with(Vec::new)
-            .push(relation);
-    }
+    let mut dfg_sets: HashMap<String, ObjectTypeDfg> = HashMap::new();
 
-    // Process each object type
-    for (otype, otype_relations) in grouped_by_otype {
-        let mut dfg: HashMap<(String, String), usize> = HashMap::new();
-        let mut start_activities: HashSet<String> = HashSet::new();
-        let mut end_activities: HashSet<String> = HashSet::new();
-
-        // Group relations by object ID (oid) within this object type
-        let mut grouped_relations: HashMap<String, Vec<&(String, String, String, String, String)>> = HashMap::new();
-        
-        for relation in &otype_relations {
-            grouped_relations
-                .entry(relation.3.clone()) // oid is at index 3
-                .or_insert_with(Vec::new)
-                .push(*relation);
-        }
+    // Every object has a single type, so the per-object traces can be bucketed by type directly.
+    // Each edge collects its waiting times; their number is the edge count
+    let mut per_otype: Vec<(HashMap<(ActivityId, ActivityId), Vec<f64>>, HashSet<ActivityId>, HashSet<ActivityId>)> =
+        vec![Default::default(); index.object_types.len()];
 
-        // Sort object IDs to ensure deterministic processing order
-        let mut sorted_oids: Vec<_> = grouped_relations.keys().cloned().collect();
-        sorted_oids.sort();
-
-        // Process each group of relations for the same object ID in sorted order
-        for oid in sorted_oids {
-            let group = grouped_relations.get(&oid).unwrap();
-            
-            // Remove duplicates based on eid (keep first occurrence)
-            let mut seen_eids: HashSet<String> = HashSet::new();
-            let mut unique_relations: Vec<&(String, String, String, String, String)> = Vec::new();
-            
-            for relation in group {
-                if seen_eids.insert(relation.0.clone()) { // eid is at index 0
-                    unique_relations.push(relation);
-                }
-            }
+    for group in index.relations_by_object() {
+        let (dfg, start_activities, end_activities) = &mut per_otype[group[0].object_type as usize];
 
-            // Sort by timestamp (index 2)
-            unique_relations.sort_by(|a, b| a.2.cmp(&b.2));
+        // Remove duplicates based on eid (keep first occurrence)
+        let mut seen_eids: HashSet<EventId> = HashSet::new();
+        let mut unique_relations = Vec::new();
 
-            // Skip empty groups or groups with only one event
-            if unique_relations.is_empty() {
-                continue;
+        for relation in group {
+            if seen_eids.insert(relation.event) {
+                unique_relations.push(relation);
             }
+        }
+
+        // Add start activity (first event after sorting)
+        start_activities.insert(unique_relations[0].activity);
 
-            // Add start activity (first event after sorting)
-            let start_activity = &unique_relations[0].1; // activity is at index 1
-            start_activities.insert(start_activity.clone());
-
-            // Add end activity (last event after sorting)
-            let end_activity = &unique_relations[unique_relations.len() - 1].1;
-            end_activities.insert(end_activity.clone());
-
-            // Create directly follows relationships
-            for i in 0..unique_relations.len() - 1 {
-                let current_relation = unique_relations[i];
-                let next_relation = unique_relations[i + 1];
-                
-                let current_activity = &current_relation.1;
-                let next_activity = &next_relation.1;
-                let current_otype = &current_relation.4;
-                let _next_otype = &next_relation.4;
-
-                // Check divergence condition before adding to DFG
-                let should_skip = if let (Some(current_divergent), Some(next_divergent)) = (
-                    divergent_objects.get(current_activity),
-                    divergent_objects.get(next_activity),
-                ) {
-                    current_divergent.contains(current_otype) && next_divergent.contains(current_otype)
-                } else {
-                    false
-                };
-
-                // Add to DFG if not divergent
-                if !should_skip {
-                    let edge = (current_activity.clone(), next_activity.clone());
-                    *dfg.entry(edge).or_insert(0) += 1;
-                }
+        // Add end activity (last event after sorting)
+        end_activities.insert(unique_relations[unique_relations.len() - 1].activity);
+
+        // Create directly follows relationships
+        for pair in unique_relations.windows(2) {
+            let current_activity = pair[0].activity;
+            let next_activity = pair[1].activity;
+            let current_otype = pair[0].object_type;
+
+            // Check divergence condition before adding to DFG
+            let should_skip = divergent_objects[current_activity as usize].contains(&current_otype)
+                && divergent_objects[next_activity as usize].contains(&current_otype);
+
+            // Add to DFG if not divergent
+            if !should_skip {
+                dfg.entry((current_activity, next_activity))
+                    .or_default()
+                    .push(waiting_seconds(pair[0], pair[1]));
             }
         }
+    }
 
-        // Store the DFG for this object type
-        dfg_sets.insert(otype, (dfg, start_activities, end_activities));
+    // Store the DFG for each object type that has relations
+    for (otype, (dfg, start_activities, end_activities)) in per_otype.into_iter().enumerate() {
+        if start_activities.is_empty() {
+            continue;
+        }
+        let mut object_type_dfg = ObjectTypeDfg {
+            start_activities: start_activities.into_iter().map(|a| index.activity(a).to_string()).collect(),
+            end_activities: end_activities.into_iter().map(|a| index.activity(a).to_string()).collect(),
+            ..Default::default()
+        };
+        for ((a, b), durations) in dfg {
+            let edge = (index.activity(a).to_string(), index.activity(b).to_string());
+            object_type_dfg.dfg.insert(edge.clone(), durations.len());
+            if let Some(stats) = WaitingTimeStats::from_values(durations) {
+                object_type_dfg.waiting_times.insert(edge, stats);
+            }
+        }
+        dfg_sets.insert(index.object_type(otype as u32).to_string(), object_type_dfg);
     }
 
     dfg_sets
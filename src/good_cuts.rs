@@ -121,7 +121,8 @@ pub struct ActivityPartitioner {
 
 impl ActivityPartitioner {
     pub fn new(dfg: &HashMap<(String, String), usize>, all_activities: &HashSet<String>) -> Self {
-        let activities: Vec<String> = all_activities.iter().cloned().collect();
+        let mut activities: Vec<String> = all_activities.iter().cloned().collect();
+        activities.sort();
         let n = activities.len();
         
         let mut activity_to_idx = HashMap::new();
@@ -1,230 +1,717 @@
-use std::collections::{HashMap, HashSet};
-use crate::types::{OCEL};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-type Relation = (String, String, String, String, String); // (eid, activity, timestamp, oid, otype)
+use serde::{Deserialize, Serialize};
 
-fn sort_hashmap_values(map: HashMap<String, HashSet<String>>) -> HashMap<String, Vec<String>> {
+use crate::event_log_index::{ActivityId, EventId, EventLogIndex, ObjectId, ObjectTypeId};
+use crate::ocel_stream::{CompactOCEL, UNDECLARED};
+
+/// Divergent, convergent, related and deficient object types per activity (each list sorted),
+/// then all activities and all object types (sorted)
+pub type InteractionPatterns = (
+    HashMap<String, Vec<String>>,
+    HashMap<String, Vec<String>>,
+    HashMap<String, Vec<String>>,
+    HashMap<String, Vec<String>>,
+    Vec<String>,
+    Vec<String>,
+);
+
+// Resolves the interned ids back to names; patterns are returned by name
+fn sort_hashmap_values(
+    index: &EventLogIndex,
+    map: HashMap<ActivityId, HashSet<ObjectTypeId>>,
+) -> HashMap<String, Vec<String>> {
     map.into_iter()
         .map(|(k, v)| {
-            let mut sorted_vec: Vec<String> = v.into_iter().collect();
+            let mut sorted_vec: Vec<String> = v.into_iter().map(|otype| index.object_type(otype).to_string()).collect();
             sorted_vec.sort();
-            (k, sorted_vec)
+            (index.activity(k).to_string(), sorted_vec)
         })
         .collect()
 }
 
-pub fn get_interaction_patterns(
-    relations: &Vec<Relation>,
-    ocel: &OCEL,
-) -> (
-    HashMap<String, Vec<String>>, // divergent (sorted)
-    HashMap<String, Vec<String>>, // convergent (sorted)
-    HashMap<String, Vec<String>>, // related (sorted)
-    HashMap<String, Vec<String>>, // deficient (sorted)
-    Vec<String>,                  // set of all activities (sorted)
-    Vec<String>                   // set of all object types (sorted)
-) {
-    // Collect unique activities and object types
-    let mut all_activities: HashSet<String> = HashSet::new();
-    let mut all_object_types: HashSet<String> = HashSet::new();
-    
-    for (_, activity, _, _, otype) in relations.iter() {
-        all_activities.insert(activity.clone());
-        all_object_types.insert(otype.clone());
+/// Share of events a pattern must hold for before it counts; `0.0` (the default) means a
+/// single event is enough.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PatternThresholds {
+    pub divergent: f64,
+    pub convergent: f64,
+    pub deficient: f64,
+}
+
+impl PatternThresholds {
+    /// Builds the thresholds from the `divergent_threshold`, `convergent_threshold` and
+    /// `deficient_threshold` query parameters, each a ratio between 0 and 1.
+    pub fn from_params(divergent: Option<f64>, convergent: Option<f64>, deficient: Option<f64>) -> Result<Self, String> {
+        let check = |name: &str, value: Option<f64>| match value {
+            None => Ok(0.0),
+            Some(value) if (0.0..=1.0).contains(&value) => Ok(value),
+            Some(value) => Err(format!("{}_threshold must be between 0 and 1, got {}", name, value)),
+        };
+        Ok(PatternThresholds {
+            divergent: check("divergent", divergent)?,
+            convergent: check("convergent", convergent)?,
+            deficient: check("deficient", deficient)?,
+        })
     }
+}
+
+// A pattern holds if it shows up at all and for at least the threshold share of events
+fn holds(ratio: f64, threshold: f64) -> bool {
+    ratio > 0.0 && ratio >= threshold
+}
+
+/// How strongly each pattern holds for one related activity/object type pair
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PatternSupport {
+    pub activity: String,
+    pub object_type: String,
+    /// Events of the activity
+    pub events: usize,
+    /// Share of them with an object of the type
+    pub related: f64,
+    /// Share of those whose objects of the type also occur in events of the activity with
+    /// other companions
+    pub divergent: f64,
+    /// Share of those with several objects of the type
+    pub convergent: f64,
+    /// Share of the events of the activity without an object of the type
+    pub deficient: f64,
+}
+
+/// Which interaction patterns hold for one activity/object type pair
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct PatternCell {
+    pub related: bool,
+    pub divergent: bool,
+    pub convergent: bool,
+    pub deficient: bool,
+}
+
+/// Lays the patterns out as an activity -> object type matrix covering every pair of the log,
+/// unrelated pairs included
+pub fn pattern_matrix(patterns: &InteractionPatterns) -> BTreeMap<String, BTreeMap<String, PatternCell>> {
+    let (divergent, convergent, related, deficient, all_activities, all_object_types) = patterns;
+    let holds = |map: &HashMap<String, Vec<String>>, activity: &str, object_type: &String| {
+        map.get(activity).is_some_and(|object_types| object_types.contains(object_type))
+    };
+    all_activities
+        .iter()
+        .map(|activity| {
+            let row = all_object_types
+                .iter()
+                .map(|object_type| {
+                    let cell = PatternCell {
+                        related: holds(related, activity, object_type),
+                        divergent: holds(divergent, activity, object_type),
+                        convergent: holds(convergent, activity, object_type),
+                        deficient: holds(deficient, activity, object_type),
+                    };
+                    (object_type.clone(), cell)
+                })
+                .collect();
+            (activity.clone(), row)
+        })
+        .collect()
+}
+
+/// Derives the interaction patterns of every activity/object type pair in one pass over the
+/// events:
+///
+/// - related: some event of the activity has an object of the type
+/// - deficient: related, but not every event of the activity has one
+/// - convergent: some event of the activity has several objects of the type
+/// - divergent: the same objects of the type occur in events of the activity together with
+///   different sets of other objects
+///
+/// Only the relations of `index` count; O2O links take part once related with
+/// [`relate_o2o_linked_objects`] or propagated, see [`O2OPropagation`].
+///
+/// [`O2OPropagation`]: crate::o2o_propagation::O2OPropagation
+pub fn get_interaction_patterns(index: &EventLogIndex) -> InteractionPatterns {
+    get_interaction_patterns_with_support(index, &PatternThresholds::default()).0
+}
 
-    // Initialize maps - start with all object types for each activity
-    let mut related: HashMap<String, HashSet<String>> = HashMap::new();
-    let mut divergent: HashMap<String, HashSet<String>> = HashMap::new();
-    let mut convergent: HashMap<String, HashSet<String>> = HashMap::new();
-    let mut deficient: HashMap<String, HashSet<String>> = HashMap::new();
+/// Same as [`get_interaction_patterns`], but divergent, convergent and deficient only count
+/// once they hold for the threshold share of events. Also returns the share for every
+/// related pair, sorted by activity and object type.
+pub fn get_interaction_patterns_with_support(
+    index: &EventLogIndex,
+    thresholds: &PatternThresholds,
+) -> (InteractionPatterns, Vec<PatternSupport>) {
+    // Objects and activity of every event; ids are dense, so plain vectors do as lookups
+    let mut event_objects: Vec<Vec<ObjectId>> = vec![Vec::new(); index.event_ids.len()];
+    let mut event_activity: Vec<ActivityId> = vec![0; index.event_ids.len()];
+    let mut object_type: Vec<ObjectTypeId> = vec![0; index.object_ids.len()];
+    let mut all_activities: HashSet<ActivityId> = HashSet::new();
+    let mut all_object_types: HashSet<ObjectTypeId> = HashSet::new();
 
-    for activity in &all_activities {
-        related.insert(activity.clone(), all_object_types.clone());
-        divergent.insert(activity.clone(), HashSet::new());
-        convergent.insert(activity.clone(), HashSet::new());
-        deficient.insert(activity.clone(), HashSet::new());
+    for relation in &index.relations {
+        event_objects[relation.event as usize].push(relation.object);
+        event_activity[relation.event as usize] = relation.activity;
+        object_type[relation.object as usize] = relation.object_type;
+        all_activities.insert(relation.activity);
+        all_object_types.insert(relation.object_type);
     }
 
-    // Create lookup dictionaries
-    let mut look_up_dict_activities: HashMap<String, String> = HashMap::new();
-    let mut look_up_dict_objects: HashMap<String, String> = HashMap::new();
-    
-    for (eid, activity, _, oid, otype) in relations.iter() {
-        look_up_dict_activities.insert(eid.clone(), activity.clone());
-        look_up_dict_objects.insert(oid.clone(), otype.clone());
+    let mut related: HashMap<ActivityId, HashSet<ObjectTypeId>> = HashMap::new();
+    let mut divergent: HashMap<ActivityId, HashSet<ObjectTypeId>> = HashMap::new();
+    let mut convergent: HashMap<ActivityId, HashSet<ObjectTypeId>> = HashMap::new();
+    let mut deficient: HashMap<ActivityId, HashSet<ObjectTypeId>> = HashMap::new();
+
+    for &activity in &all_activities {
+        related.insert(activity, HashSet::new());
+        divergent.insert(activity, HashSet::new());
+        convergent.insert(activity, HashSet::new());
+        deficient.insert(activity, HashSet::new());
     }
 
-    // Create identifiers structure (equivalent to Python's identifiers DataFrame)
-    let mut event_object_sets: HashMap<String, Vec<String>> = HashMap::new();
-    
-    // Group objects by event_id
-    for (eid, _, _, oid, _) in relations.iter() {
-        event_object_sets.entry(eid.clone()).or_default().push(oid.clone());
+    // Number of events per activity, and per activity and object type
+    let mut activity_event_count: HashMap<ActivityId, usize> = HashMap::new();
+    let mut type_event_count: HashMap<(ActivityId, ObjectTypeId), usize> = HashMap::new();
+    let mut convergent_event_count: HashMap<(ActivityId, ObjectTypeId), usize> = HashMap::new();
+    // (activity, object type, objects of that type) -> (event whose full object set was seen
+    // first, number of events, whether another full object set was seen)
+    type Group = (ActivityId, ObjectTypeId, Vec<ObjectId>);
+    let mut groups: HashMap<Group, (EventId, usize, bool)> = HashMap::new();
+
+    for objects in &mut event_objects {
+        objects.sort_unstable();
+        objects.dedup();
+    }
+
+    for (event, objects) in event_objects.iter().enumerate() {
+        if objects.is_empty() {
+            continue;
+        }
+        let activity = event_activity[event];
+        *activity_event_count.entry(activity).or_insert(0) += 1;
+
+        // Objects of the event grouped by type, each group sorted like `objects`
+        let mut by_type: HashMap<ObjectTypeId, Vec<ObjectId>> = HashMap::new();
+        for &object in objects {
+            by_type.entry(object_type[object as usize]).or_default().push(object);
+        }
+
+        for (otype, objects_of_type) in by_type {
+            *type_event_count.entry((activity, otype)).or_insert(0) += 1;
+
+            if objects_of_type.len() > 1 {
+                *convergent_event_count.entry((activity, otype)).or_insert(0) += 1;
+            }
+
+            let group = groups
+                .entry((activity, otype, objects_of_type))
+                .or_insert((event as EventId, 0, false));
+            group.1 += 1;
+            // Divergent once the same objects of the type show up with other companions
+            if event_objects[group.0 as usize] != *objects {
+                group.2 = true;
+            }
+        }
     }
+
+    // Events of all groups that were seen with different companions
+    let mut divergent_event_count: HashMap<(ActivityId, ObjectTypeId), usize> = HashMap::new();
+    for ((activity, otype, _), (_, events, diverges)) in groups {
+        if diverges {
+            *divergent_event_count.entry((activity, otype)).or_insert(0) += events;
+        }
+    }
+
+    let mut support = Vec::with_capacity(type_event_count.len());
+    for ((activity, otype), count) in type_event_count {
+        let total = activity_event_count[&activity];
+        let ratio_of = |counts: &HashMap<(ActivityId, ObjectTypeId), usize>| {
+            counts.get(&(activity, otype)).copied().unwrap_or(0) as f64 / count as f64
+        };
+        let pair = PatternSupport {
+            activity: index.activity(activity).to_string(),
+            object_type: index.object_type(otype).to_string(),
+            events: total,
+            related: count as f64 / total as f64,
+            divergent: ratio_of(&divergent_event_count),
+            convergent: ratio_of(&convergent_event_count),
+            deficient: (total - count) as f64 / total as f64,
+        };
+
+        related.get_mut(&activity).unwrap().insert(otype);
+        if holds(pair.divergent, thresholds.divergent) {
+            divergent.get_mut(&activity).unwrap().insert(otype);
+        }
+        if holds(pair.convergent, thresholds.convergent) {
+            convergent.get_mut(&activity).unwrap().insert(otype);
+        }
+        if holds(pair.deficient, thresholds.deficient) {
+            deficient.get_mut(&activity).unwrap().insert(otype);
+        }
+        support.push(pair);
+    }
+    support.sort_by(|a, b| (&a.activity, &a.object_type).cmp(&(&b.activity, &b.object_type)));
+
+     // Convert HashSets to sorted Vecs before returning
+    let divergent_sorted = sort_hashmap_values(index, divergent);
+    let convergent_sorted = sort_hashmap_values(index, convergent);
+    let related_sorted = sort_hashmap_values(index, related);
+    let deficient_sorted = sort_hashmap_values(index, deficient);
     
-    // Sort object sets for each event (equivalent to tuple(sorted(set(...))))
-    let mut identifiers: HashMap<String, (Vec<String>, String)> = HashMap::new();
-    for (eid, mut oids) in event_object_sets {
-        oids.sort();
-        oids.dedup(); // Remove duplicates
-        let activity = look_up_dict_activities.get(&eid).unwrap().clone();
-        identifiers.insert(eid, (oids, activity));
-    }
-
-    // Check for deficient object types (same as original logic)
-    let mut activity_events: HashMap<String, HashSet<String>> = HashMap::new();
-    let mut activity_object_type_events: HashMap<(String, String), HashSet<String>> = HashMap::new();
-
-    for (eid, activity, _, _, otype) in relations.iter() {
-        activity_events.entry(activity.clone()).or_default().insert(eid.clone());
-        activity_object_type_events
-            .entry((activity.clone(), otype.clone()))
-            .or_default()
-            .insert(eid.clone());
-    }
-
-    for activity in &all_activities {
-        if let Some(total_events) = activity_events.get(activity) {
-            let total_event_count = total_events.len();
-            
-            for otype in &all_object_types {
-                let key = (activity.clone(), otype.clone());
-                
-                if let Some(otype_events) = activity_object_type_events.get(&key) {
-                    let otype_event_count = otype_events.len();
-                    
-                    if otype_event_count != total_event_count {
-                        if otype_event_count > 0 {
-                            deficient.get_mut(activity).unwrap().insert(otype.clone());
-                        } else {
-                            related.get_mut(activity).unwrap().remove(otype);
-                        }
-                    }
-                } else {
-                    related.get_mut(activity).unwrap().remove(otype);
+    // Also sort the activity and object type sets
+    let mut all_activities_sorted: Vec<String> = all_activities.into_iter().map(|a| index.activity(a).to_string()).collect();
+    all_activities_sorted.sort();
+    
+    let mut all_object_types_sorted: Vec<String> = all_object_types.into_iter().map(|o| index.object_type(o).to_string()).collect();
+    all_object_types_sorted.sort();
+
+    (
+        (divergent_sorted, convergent_sorted, related_sorted, deficient_sorted, all_activities_sorted, all_object_types_sorted),
+        support,
+    )
+}
+
+/// Relates every event to the objects O2O-linked to its own objects, for the object types its
+/// activity has no E2O relationship to anywhere in the log. An association the log records
+/// only as O2O (items in a package, with only the package on the packing event) then shows up
+/// in the patterns, traces and DFG, while the types an activity already names keep just the
+/// objects recorded for it. Links are followed both ways, one hop far, and the new
+/// relationships carry the O2O qualifier. Returns the number of relationships added.
+pub fn relate_o2o_linked_objects(compact: &mut CompactOCEL) -> usize {
+    let declared = |object: u32| compact.objects[object as usize].object_type != UNDECLARED;
+
+    let mut e2o_types: HashSet<(u32, u32)> = HashSet::new();
+    for event in &compact.events {
+        for &(object, _) in event.relationships.iter().filter(|(object, _)| declared(*object)) {
+            e2o_types.insert((event.event_type, compact.objects[object as usize].object_type));
+        }
+    }
+
+    // object -> (linked object, qualifier)
+    let mut linked: HashMap<u32, Vec<(u32, u32)>> = HashMap::new();
+    for (source, object) in compact.objects.iter().enumerate().filter(|(_, object)| object.object_type != UNDECLARED) {
+        let source = source as u32;
+        for &(target, qualifier) in object.relationships.iter().filter(|(target, _)| declared(*target) && *target != source) {
+            linked.entry(source).or_default().push((target, qualifier));
+            linked.entry(target).or_default().push((source, qualifier));
+        }
+    }
+
+    let objects = &compact.objects;
+    let mut added = 0;
+    for event in &mut compact.events {
+        let mut related: HashSet<u32> = event.relationships.iter().map(|(object, _)| *object).collect();
+        let mut inherited = Vec::new();
+        for (object, _) in &event.relationships {
+            for &(linked_object, qualifier) in linked.get(object).into_iter().flatten() {
+                let object_type = objects[linked_object as usize].object_type;
+                if !e2o_types.contains(&(event.event_type, object_type)) && related.insert(linked_object) {
+                    inherited.push((linked_object, qualifier));
                 }
             }
         }
+        added += inherited.len();
+        event.relationships.extend(inherited);
     }
+    added
+}
 
-    // Create object type identifiers for each event (equivalent to Python's object_type columns)
-    let mut event_object_type_sets: HashMap<String, HashMap<String, Vec<String>>> = HashMap::new();
-    
-    for (event_id, (all_objects, _)) in &identifiers {
-        let mut type_sets: HashMap<String, Vec<String>> = HashMap::new();
-        
-        for otype in &all_object_types {
-            let mut objects_of_type: Vec<String> = all_objects
-                .iter()
-                .filter(|&oid| look_up_dict_objects.get(oid).unwrap() == otype)
-                .cloned()
-                .collect();
-            objects_of_type.sort();
-            type_sets.insert(otype.clone(), objects_of_type);
-        }
-        
-        event_object_type_sets.insert(event_id.clone(), type_sets);
-    }
-
-    // Analyze patterns for each object type and activity
-    for otype in &all_object_types {
-        // Filter events that have objects of this type
-        let events_with_otype: Vec<(&String, &(Vec<String>, String))> = identifiers
-            .iter()
-            .filter(|(event_id, _)| {
-                event_object_type_sets
-                    .get(*event_id)
-                    .unwrap()
-                    .get(otype)
-                    .unwrap()
-                    .len() > 0
-            })
-            .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::add_self_loops;
+    use crate::build_relations_fns::build_relations;
+    use crate::event_log_index::TieBreak;
+    use crate::ocel_import;
+    use crate::ocel_stream;
+    use crate::types::{OCELEvent, OCELObject, OCELRelationship, OCEL};
+
+    // The implementation before the single-pass rewrite, kept as the reference
+    fn legacy_get_interaction_patterns(index: &EventLogIndex) -> InteractionPatterns {
+        // Collect unique activities and object types
+        let mut all_activities: HashSet<ActivityId> = HashSet::new();
+        let mut all_object_types: HashSet<ObjectTypeId> = HashSet::new();
+
+        for relation in &index.relations {
+            all_activities.insert(relation.activity);
+            all_object_types.insert(relation.object_type);
+        }
+
+        // Initialize maps - start with all object types for each activity
+        let mut related: HashMap<ActivityId, HashSet<ObjectTypeId>> = HashMap::new();
+        let mut divergent: HashMap<ActivityId, HashSet<ObjectTypeId>> = HashMap::new();
+        let mut convergent: HashMap<ActivityId, HashSet<ObjectTypeId>> = HashMap::new();
+        let mut deficient: HashMap<ActivityId, HashSet<ObjectTypeId>> = HashMap::new();
+
+        for &activity in &all_activities {
+            related.insert(activity, all_object_types.clone());
+            divergent.insert(activity, HashSet::new());
+            convergent.insert(activity, HashSet::new());
+            deficient.insert(activity, HashSet::new());
+        }
+
+        // Create lookup dictionaries
+        let mut look_up_dict_activities: HashMap<EventId, ActivityId> = HashMap::new();
+        let mut look_up_dict_objects: HashMap<ObjectId, ObjectTypeId> = HashMap::new();
+
+        for relation in &index.relations {
+            look_up_dict_activities.insert(relation.event, relation.activity);
+            look_up_dict_objects.insert(relation.object, relation.object_type);
+        }
+
+        // Create identifiers structure (equivalent to Python's identifiers DataFrame)
+        let mut event_object_sets: HashMap<EventId, Vec<ObjectId>> = HashMap::new();
+
+        // Group objects by event_id
+        for relation in &index.relations {
+            event_object_sets.entry(relation.event).or_default().push(relation.object);
+        }
+
+        // Sort object sets for each event (equivalent to tuple(sorted(set(...))))
+        let mut identifiers: HashMap<EventId, (Vec<ObjectId>, ActivityId)> = HashMap::new();
+        for (eid, mut oids) in event_object_sets {
+            oids.sort();
+            oids.dedup(); // Remove duplicates
+            let activity = *look_up_dict_activities.get(&eid).unwrap();
+            identifiers.insert(eid, (oids, activity));
+        }
+
+        // Check for deficient object types (same as original logic)
+        let mut activity_events: HashMap<ActivityId, HashSet<EventId>> = HashMap::new();
+        let mut activity_object_type_events: HashMap<(ActivityId, ObjectTypeId), HashSet<EventId>> = HashMap::new();
+
+        for relation in &index.relations {
+            activity_events.entry(relation.activity).or_default().insert(relation.event);
+            activity_object_type_events
+                .entry((relation.activity, relation.object_type))
+                .or_default()
+                .insert(relation.event);
+        }
 
         for activity in &all_activities {
-            let activity_events_with_otype: Vec<&(&String, &(Vec<String>, String))> = events_with_otype
-                .iter()
-                .filter(|(_, (_, act))| act == activity)
-                .collect();
+            if let Some(total_events) = activity_events.get(activity) {
+                let total_event_count = total_events.len();
 
-            if activity_events_with_otype.is_empty() {
-                continue;
+                for otype in &all_object_types {
+                    let key = (*activity, *otype);
+
+                    if let Some(otype_events) = activity_object_type_events.get(&key) {
+                        let otype_event_count = otype_events.len();
+
+                        if otype_event_count != total_event_count {
+                            if otype_event_count > 0 {
+                                deficient.get_mut(activity).unwrap().insert(*otype);
+                            } else {
+                                related.get_mut(activity).unwrap().remove(otype);
+                            }
+                        }
+                    } else {
+                        related.get_mut(activity).unwrap().remove(otype);
+                    }
+                }
             }
+        }
+
+        // Create object type identifiers for each event (equivalent to Python's object_type columns)
+        let mut event_object_type_sets: HashMap<EventId, HashMap<ObjectTypeId, Vec<ObjectId>>> = HashMap::new();
+
+        for (event_id, (all_objects, _)) in &identifiers {
+            let mut type_sets: HashMap<ObjectTypeId, Vec<ObjectId>> = HashMap::new();
 
-            // Check for convergent pattern: one event with multiple objects of same type
-            let has_convergent = activity_events_with_otype
+            for otype in &all_object_types {
+                let mut objects_of_type: Vec<ObjectId> = all_objects
+                    .iter()
+                    .filter(|&oid| look_up_dict_objects.get(oid).unwrap() == otype)
+                    .cloned()
+                    .collect();
+                objects_of_type.sort();
+                type_sets.insert(*otype, objects_of_type);
+            }
+
+            event_object_type_sets.insert(*event_id, type_sets);
+        }
+
+        // Analyze patterns for each object type and activity
+        for otype in &all_object_types {
+            // Filter events that have objects of this type
+            let events_with_otype: Vec<(&EventId, &(Vec<ObjectId>, ActivityId))> = identifiers
                 .iter()
-                .any(|(event_id, _)| {
+                .filter(|(event_id, _)| {
                     event_object_type_sets
                         .get(*event_id)
                         .unwrap()
                         .get(otype)
                         .unwrap()
-                        .len() > 1
-                });
+                        .len() > 0
+                })
+                .collect();
 
-            if has_convergent {
-                convergent.get_mut(activity).unwrap().insert(otype.clone());
-            }
+            for activity in &all_activities {
+                let activity_events_with_otype: Vec<&(&EventId, &(Vec<ObjectId>, ActivityId))> = events_with_otype
+                    .iter()
+                    .filter(|(_, (_, act))| act == activity)
+                    .collect();
 
-            // Check for divergent pattern: same object set appears in multiple events
-            // Group events by their object sets of this type
-            let mut object_set_to_events: HashMap<Vec<String>, Vec<String>> = HashMap::new();
-            
-            for (event_id, _) in &activity_events_with_otype {
-                let object_set = event_object_type_sets
-                                    .get(*event_id)
-                                    .unwrap()
-                                    .get(otype)
-                                    .unwrap()
-                                    .clone();
-                
-                if !object_set.is_empty() {
-                    object_set_to_events
-                        .entry(object_set)
-                        .or_default()
-                        .push((**event_id).clone());
+                if activity_events_with_otype.is_empty() {
+                    continue;
+                }
+
+                // Check for convergent pattern: one event with multiple objects of same type
+                let has_convergent = activity_events_with_otype
+                    .iter()
+                    .any(|(event_id, _)| {
+                        event_object_type_sets
+                            .get(*event_id)
+                            .unwrap()
+                            .get(otype)
+                            .unwrap()
+                            .len() > 1
+                    });
+
+                if has_convergent {
+                    convergent.get_mut(activity).unwrap().insert(*otype);
                 }
-            }
 
-            // Count unique "all" object sets for each object set of this type
-            let mut matches: HashMap<Vec<String>, HashSet<Vec<String>>> = HashMap::new();
-            
-            for (object_set, event_ids) in &object_set_to_events {
-                if !object_set.is_empty() {
-                    let mut unique_all_sets = HashSet::new();
-                    for event_id in event_ids {
-                        let all_objects = &identifiers.get(event_id).unwrap().0;
-                        unique_all_sets.insert(all_objects.clone());
+                // Check for divergent pattern: same object set appears in multiple events
+                // Group events by their object sets of this type
+                let mut object_set_to_events: HashMap<Vec<ObjectId>, Vec<EventId>> = HashMap::new();
+
+                for (event_id, _) in &activity_events_with_otype {
+                    let object_set = event_object_type_sets
+                                        .get(*event_id)
+                                        .unwrap()
+                                        .get(otype)
+                                        .unwrap()
+                                        .clone();
+
+                    if !object_set.is_empty() {
+                        object_set_to_events
+                            .entry(object_set)
+                            .or_default()
+                            .push(**event_id);
+                    }
+                }
+
+                // Count unique "all" object sets for each object set of this type
+                let mut matches: HashMap<Vec<ObjectId>, HashSet<Vec<ObjectId>>> = HashMap::new();
+
+                for (object_set, event_ids) in &object_set_to_events {
+                    if !object_set.is_empty() {
+                        let mut unique_all_sets = HashSet::new();
+                        for event_id in event_ids {
+                            let all_objects = &identifiers.get(event_id).unwrap().0;
+                            unique_all_sets.insert(all_objects.clone());
+                        }
+                        matches.insert(object_set.clone(), unique_all_sets);
                     }
-                    matches.insert(object_set.clone(), unique_all_sets);
+                }
+
+                // Check if any object set appears with multiple different "all" sets
+                let has_divergent = matches
+                    .values()
+                    .any(|unique_all_sets| unique_all_sets.len() > 1);
+
+                if has_divergent {
+                    divergent.get_mut(activity).unwrap().insert(*otype);
                 }
             }
+        }
+
+         // Convert HashSets to sorted Vecs before returning
+        let divergent_sorted = sort_hashmap_values(index, divergent);
+        let convergent_sorted = sort_hashmap_values(index, convergent);
+        let related_sorted = sort_hashmap_values(index, related);
+        let deficient_sorted = sort_hashmap_values(index, deficient);
+
+        // Also sort the activity and object type sets
+        let mut all_activities_sorted: Vec<String> = all_activities.into_iter().map(|a| index.activity(a).to_string()).collect();
+        all_activities_sorted.sort();
+
+        let mut all_object_types_sorted: Vec<String> = all_object_types.into_iter().map(|o| index.object_type(o).to_string()).collect();
+        all_object_types_sorted.sort();
+
+        (divergent_sorted, convergent_sorted, related_sorted, deficient_sorted, all_activities_sorted, all_object_types_sorted)
+    }
+
+    fn assert_same_patterns(index: &EventLogIndex, log: &str) {
+        assert_eq!(
+            get_interaction_patterns(index),
+            legacy_get_interaction_patterns(index),
+            "interaction patterns differ on {}",
+            log
+        );
+    }
 
-            // Check if any object set appears with multiple different "all" sets
-            let has_divergent = matches
-                .values()
-                .any(|unique_all_sets| unique_all_sets.len() > 1);
+    // Small order-to-delivery log that has every pattern for some pairs but not for others:
+    // several items per order (convergent), customers and trucks shared between orders
+    // (divergent), payments only sometimes naming the customer (deficient), a duplicated
+    // relationship and an event without objects
+    fn generated_log() -> OCEL {
+        let mut objects = Vec::new();
+        let mut events = Vec::new();
+        let start = chrono::DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap();
+        let mut event = |activity: &str, object_ids: Vec<String>| {
+            let minutes = events.len() as i64;
+            events.push(OCELEvent {
+                id: format!("e{}", events.len()),
+                event_type: activity.to_string(),
+                time: start + chrono::Duration::minutes(minutes),
+                attributes: Vec::new(),
+                relationships: object_ids
+                    .into_iter()
+                    .map(|object_id| OCELRelationship { object_id, qualifier: String::new() })
+                    .collect(),
+            });
+        };
+        let mut object = |id: String, object_type: &str| {
+            objects.push(OCELObject { id: id.clone(), object_type: object_type.to_string(), attributes: Vec::new(), relationships: Vec::new() });
+            id
+        };
 
-            if has_divergent {
-                divergent.get_mut(activity).unwrap().insert(otype.clone());
+        let customers: Vec<String> = (0..7).map(|c| object(format!("c{}", c), "customer")).collect();
+        let trucks: Vec<String> = (0..4).map(|t| object(format!("t{}", t), "truck")).collect();
+        for k in 0..40 {
+            let order = object(format!("o{}", k), "order");
+            let items: Vec<String> = (0..1 + k % 3).map(|i| object(format!("i{}-{}", k, i), "item")).collect();
+            let customer = customers[k % customers.len()].clone();
+
+            event("place", [vec![order.clone(), customer.clone()], items.clone()].concat());
+            for item in &items {
+                event("pick", vec![item.clone()]);
+            }
+            let mut packed = [vec![order.clone()], items.clone()].concat();
+            if k == 4 {
+                packed.push(order.clone());
+            }
+            event("pack", packed);
+            if k % 3 != 0 {
+                event("pay", if k % 2 == 0 { vec![order.clone(), customer] } else { vec![order.clone()] });
+            }
+            event("load", vec![trucks[k % trucks.len()].clone(), order.clone()]);
+            if k % 10 == 9 {
+                event("cancel", vec![order]);
             }
         }
+        event("cancel", Vec::new());
+
+        OCEL { event_types: Vec::new(), object_types: Vec::new(), events, objects }
     }
 
-     // Convert HashSets to sorted Vecs before returning
-    let divergent_sorted = sort_hashmap_values(divergent);
-    let convergent_sorted = sort_hashmap_values(convergent);
-    let related_sorted = sort_hashmap_values(related);
-    let deficient_sorted = sort_hashmap_values(deficient);
-    
-    // Also sort the activity and object type sets
-    let mut all_activities_sorted: Vec<String> = all_activities.into_iter().collect();
-    all_activities_sorted.sort();
-    
-    let mut all_object_types_sorted: Vec<String> = all_object_types.into_iter().collect();
-    all_object_types_sorted.sort();
+    #[test]
+    fn matches_previous_implementation_on_generated_log() {
+        let ocel = generated_log();
+        let index = build_relations(&ocel.events, &ocel.objects, &TieBreak::default());
+        assert_same_patterns(&index, "the generated log");
+    }
 
-    (divergent_sorted, convergent_sorted, related_sorted, deficient_sorted, all_activities_sorted, all_object_types_sorted)
+    #[test]
+    #[ignore = "needs OCEL logs in data/, which is not committed; run with --ignored after adding some"]
+    fn matches_previous_implementation_on_data_logs() {
+        let entries = std::fs::read_dir("data").expect("data/ should hold the logs to compare on");
+        let mut compared = 0;
+        for path in entries.map(|entry| entry.unwrap().path()) {
+            // Flat tables are imported through another path
+            if ocel_import::format_from_extension(&path).is_none() {
+                continue;
+            }
+            let ocel = ocel_import::import_ocel_path(&path)
+                .unwrap_or_else(|error| panic!("could not import {}: {}", path.display(), error));
+            compared += 1;
+            let index = build_relations(&ocel.events, &ocel.objects, &TieBreak::default());
+            assert_same_patterns(&index, &path.display().to_string());
+        }
+        assert!(compared > 0, "no OCEL log found in data/");
+    }
+
+    #[test]
+    fn thresholds_drop_infrequent_patterns() {
+        let ocel = generated_log();
+        let index = build_relations(&ocel.events, &ocel.objects, &TieBreak::default());
+        let support_of = |support: &[PatternSupport], activity: &str, object_type: &str| {
+            support
+                .iter()
+                .find(|pair| pair.activity == activity && pair.object_type == object_type)
+                .cloned()
+                .unwrap()
+        };
+
+        // Half of the payments have a customer, two thirds of the packings several items
+        let (patterns, support) = get_interaction_patterns_with_support(&index, &PatternThresholds::default());
+        assert_eq!(patterns, get_interaction_patterns(&index));
+        let pay = support_of(&support, "pay", "customer");
+        assert_eq!((pay.events, pay.deficient, pay.related), (26, 0.5, 0.5));
+        assert_eq!(support_of(&support, "pack", "item").convergent, 0.65);
+        assert!(patterns.3["pay"].contains(&"customer".to_string()));
+        assert!(patterns.1["pack"].contains(&"item".to_string()));
+
+        let thresholds = PatternThresholds::from_params(None, Some(0.7), Some(0.6)).unwrap();
+        let (patterns, thresholded_support) = get_interaction_patterns_with_support(&index, &thresholds);
+        assert_eq!(support, thresholded_support);
+        assert!(!patterns.3["pay"].contains(&"customer".to_string()));
+        assert!(!patterns.1["pack"].contains(&"item".to_string()));
+        // Related pairs do not depend on the thresholds
+        assert!(patterns.2["pay"].contains(&"customer".to_string()));
+
+        assert!(PatternThresholds::from_params(Some(1.5), None, None).is_err());
+    }
+
+    // Items are picked on their own and only linked to their package through O2O; packing and
+    // shipping name the package alone
+    fn packed_items_log() -> OCEL {
+        let start = chrono::DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap();
+        let mut objects = Vec::new();
+        let mut events = Vec::new();
+        let relationship = |object_id: &str| OCELRelationship { object_id: object_id.to_string(), qualifier: "packed in".to_string() };
+        for p in 0..4 {
+            let package = format!("p{}", p);
+            for i in 0..2 + p % 2 {
+                let item = format!("i{}-{}", p, i);
+                objects.push(OCELObject { id: item.clone(), object_type: "item".to_string(), attributes: Vec::new(), relationships: vec![relationship(&package)] });
+                events.push(("pick", item));
+            }
+            objects.push(OCELObject { id: package.clone(), object_type: "package".to_string(), attributes: Vec::new(), relationships: Vec::new() });
+            events.push(("pack", package.clone()));
+            events.push(("ship", package));
+        }
+        let events = events
+            .into_iter()
+            .enumerate()
+            .map(|(e, (activity, object))| OCELEvent {
+                id: format!("e{}", e),
+                event_type: activity.to_string(),
+                time: start + chrono::Duration::minutes(e as i64),
+                attributes: Vec::new(),
+                relationships: vec![relationship(&object)],
+            })
+            .collect();
+        OCEL { event_types: Vec::new(), object_types: Vec::new(), events, objects }
+    }
+
+    #[test]
+    fn o2o_only_associations_reach_patterns_and_traces() {
+        let compact = || {
+            let mut compact = CompactOCEL::new();
+            ocel_stream::feed_ocel(packed_items_log(), &mut compact);
+            compact
+        };
+        let index = EventLogIndex::from_compact(compact(), &TieBreak::default());
+        let item_traces = |index: &EventLogIndex| -> HashSet<Vec<String>> {
+            let divergent = get_interaction_patterns(index).0;
+            add_self_loops::get_traces_from_index(index, &divergent)
+                .into_iter()
+                .filter(|trace| trace.object_type == "item")
+                .map(|trace| trace.activities)
+                .collect()
+        };
+        assert_eq!(item_traces(&index), HashSet::from([vec!["pick".to_string()]]));
+
+        // Each of the 10 picks gains the package, packing and shipping each of the 10 items
+        let mut compact = compact();
+        assert_eq!(relate_o2o_linked_objects(&mut compact), 3 * 10);
+        let index = EventLogIndex::from_compact(compact, &TieBreak::default());
+        let (divergent, convergent, related, _, _, _) = get_interaction_patterns(&index);
+        assert_eq!(related["pack"], ["item", "package"]);
+        assert_eq!(convergent["pack"], ["item"]);
+        assert_eq!(divergent["pick"], ["package"]);
+        // Each item now directly precedes the packing of its package, so pick -> pack -> ship
+        let activities = ["pick", "pack", "ship"].map(String::from).to_vec();
+        assert_eq!(item_traces(&index), HashSet::from([activities]));
+    }
 }
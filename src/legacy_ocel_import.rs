@@ -0,0 +1,502 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs as stdfs;
+use std::io::BufReader;
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::types::{
+    parse_timestamp, OCELAttributeValue, OCELEvent, OCELEventAttribute, OCELObject, OCELObjectAttribute,
+    OCELRelationship, OCELType, OCELTypeAttribute, OCEL,
+};
+
+// OCEL 1.0 has no qualifiers and no attribute timestamps; these are the
+// placeholders used when upgrading to the OCEL 2.0 model.
+const DEFAULT_QUALIFIER: &str = "";
+const INITIAL_ATTRIBUTE_TIME: &str = "1970-01-01T00:00:00+00:00";
+
+/// An OCEL 1.0 event before it is upgraded
+pub(crate) struct LegacyEvent {
+    pub id: String,
+    pub activity: String,
+    pub timestamp: DateTime<FixedOffset>,
+    /// Related object ids with their qualifier (empty for OCEL 1.0 logs)
+    pub omap: Vec<(String, String)>,
+    pub vmap: Vec<(String, OCELAttributeValue)>,
+}
+
+/// An OCEL 1.0 object before it is upgraded
+pub(crate) struct LegacyObject {
+    pub id: String,
+    pub object_type: String,
+    pub ovmap: Vec<(String, OCELAttributeValue)>,
+}
+
+/// Flat, OCEL 1.0 shaped log (events with an object map) that is upgraded to [`OCEL`]
+#[derive(Default)]
+pub(crate) struct LegacyLog {
+    pub declared_object_types: Vec<String>,
+    pub events: Vec<LegacyEvent>,
+    pub objects: Vec<LegacyObject>,
+}
+
+// ---------------------------------------------------------------------------
+// JSON
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct Ocel1Json {
+    #[serde(rename = "ocel:global-log", default)]
+    global_log: Option<Ocel1GlobalLog>,
+    #[serde(rename = "ocel:events", default, deserialize_with = "ordered_map")]
+    events: Vec<(String, Ocel1JsonEvent)>,
+    #[serde(rename = "ocel:objects", default, deserialize_with = "ordered_map")]
+    objects: Vec<(String, Ocel1JsonObject)>,
+}
+
+#[derive(Deserialize)]
+struct Ocel1GlobalLog {
+    #[serde(rename = "ocel:object-types", default)]
+    object_types: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Ocel1JsonEvent {
+    #[serde(rename = "ocel:activity")]
+    activity: String,
+    #[serde(rename = "ocel:timestamp")]
+    timestamp: String,
+    #[serde(rename = "ocel:omap", default)]
+    omap: Vec<String>,
+    #[serde(rename = "ocel:vmap", default, deserialize_with = "ordered_map")]
+    vmap: Vec<(String, serde_json::Value)>,
+}
+
+#[derive(Deserialize)]
+struct Ocel1JsonObject {
+    #[serde(rename = "ocel:type")]
+    object_type: String,
+    #[serde(rename = "ocel:ovmap", default, deserialize_with = "ordered_map")]
+    ovmap: Vec<(String, serde_json::Value)>,
+}
+
+// Events and objects are keyed by id in OCEL 1.0 JSON; keep them in file order
+fn ordered_map<'de, D, V>(deserializer: D) -> Result<Vec<(String, V)>, D::Error>
+where
+    D: Deserializer<'de>,
+    V: Deserialize<'de>,
+{
+    struct OrderedMapVisitor<V>(std::marker::PhantomData<V>);
+
+    impl<'de, V: Deserialize<'de>> Visitor<'de> for OrderedMapVisitor<V> {
+        type Value = Vec<(String, V)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map keyed by id")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+            Ok(entries)
+        }
+    }
+
+    deserializer.deserialize_map(OrderedMapVisitor(std::marker::PhantomData))
+}
+
+fn json_to_attribute_value(value: serde_json::Value) -> OCELAttributeValue {
+    match value {
+        serde_json::Value::Null => OCELAttributeValue::Null,
+        serde_json::Value::Bool(b) => OCELAttributeValue::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => OCELAttributeValue::Integer(i),
+            None => OCELAttributeValue::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => match parse_timestamp(&s) {
+            Some(dt) => OCELAttributeValue::Time(dt),
+            None => OCELAttributeValue::String(s),
+        },
+        other => OCELAttributeValue::String(other.to_string()),
+    }
+}
+
+/// Reads an OCEL 1.0 JSON log and upgrades it to the OCEL 2.0 model.
+pub fn import_ocel1_json(path: &Path) -> Result<OCEL, String> {
+    let file = stdfs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let raw: Ocel1Json = serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| format!("Failed to parse OCEL 1.0 JSON {}: {}", path.display(), e))?;
+
+    let mut log = LegacyLog {
+        declared_object_types: raw.global_log.map(|g| g.object_types).unwrap_or_default(),
+        ..Default::default()
+    };
+
+    for (id, event) in raw.events {
+        let timestamp = parse_timestamp(&event.timestamp)
+            .ok_or_else(|| format!("Event {} has an unparsable timestamp: {}", id, event.timestamp))?;
+        log.events.push(LegacyEvent {
+            id,
+            activity: event.activity,
+            timestamp,
+            omap: event.omap.into_iter().map(|oid| (oid, DEFAULT_QUALIFIER.to_string())).collect(),
+            vmap: event.vmap.into_iter().map(|(k, v)| (k, json_to_attribute_value(v))).collect(),
+        });
+    }
+
+    for (id, object) in raw.objects {
+        log.objects.push(LegacyObject {
+            id,
+            object_type: object.object_type,
+            ovmap: object.ovmap.into_iter().map(|(k, v)| (k, json_to_attribute_value(v))).collect(),
+        });
+    }
+
+    Ok(upgrade_to_ocel2(log))
+}
+
+// ---------------------------------------------------------------------------
+// XML
+// ---------------------------------------------------------------------------
+
+fn xml_attribute(e: &BytesStart, name: &str) -> Option<String> {
+    e.try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attr| attr.unescape_value().ok().map(|v| v.into_owned()))
+}
+
+fn xml_to_attribute_value(tag: &[u8], value: String) -> OCELAttributeValue {
+    match tag {
+        b"int" => value.parse().map(OCELAttributeValue::Integer).unwrap_or(OCELAttributeValue::String(value)),
+        b"float" => value.parse().map(OCELAttributeValue::Float).unwrap_or(OCELAttributeValue::String(value)),
+        b"boolean" => value.parse().map(OCELAttributeValue::Boolean).unwrap_or(OCELAttributeValue::String(value)),
+        b"date" => parse_timestamp(&value).map(OCELAttributeValue::Time).unwrap_or(OCELAttributeValue::String(value)),
+        _ => OCELAttributeValue::String(value),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum XmlSection {
+    None,
+    GlobalLog,
+    Event,
+    Object,
+}
+
+/// Reads an OCEL 1.0 XML log and upgrades it to the OCEL 2.0 model.
+pub fn import_ocel1_xml(path: &Path) -> Result<OCEL, String> {
+    let file = stdfs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    let mut buf = Vec::new();
+
+    let mut log = LegacyLog::default();
+    let mut section = XmlSection::None;
+    // Key of the <list> we are currently inside (omap, vmap, ovmap, object-types)
+    let mut current_list: Option<String> = None;
+
+    let mut id = String::new();
+    let mut activity = String::new();
+    let mut object_type = String::new();
+    let mut timestamp: Option<DateTime<FixedOffset>> = None;
+    let mut omap: Vec<(String, String)> = Vec::new();
+    let mut values: Vec<(String, OCELAttributeValue)> = Vec::new();
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| format!("Failed to parse OCEL 1.0 XML {}: {}", path.display(), e))?;
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let tag = e.name().as_ref().to_vec();
+                match tag.as_slice() {
+                    b"global" => {
+                        if xml_attribute(e, "scope").as_deref() == Some("log") {
+                            section = XmlSection::GlobalLog;
+                        }
+                    }
+                    b"event" => {
+                        section = XmlSection::Event;
+                        id.clear();
+                        activity.clear();
+                        timestamp = None;
+                        omap.clear();
+                        values.clear();
+                    }
+                    b"object" => {
+                        section = XmlSection::Object;
+                        id.clear();
+                        object_type.clear();
+                        values.clear();
+                    }
+                    b"list" => {
+                        if matches!(event, Event::Start(_)) {
+                            current_list = xml_attribute(e, "key");
+                        }
+                    }
+                    _ => {
+                        let key = xml_attribute(e, "key").unwrap_or_default();
+                        let value = xml_attribute(e, "value").unwrap_or_default();
+
+                        match (section, current_list.as_deref(), key.as_str()) {
+                            (XmlSection::GlobalLog, Some("object-types"), _) => {
+                                log.declared_object_types.push(value);
+                            }
+                            (XmlSection::Event, Some("omap"), _) => omap.push((value, DEFAULT_QUALIFIER.to_string())),
+                            (XmlSection::Event, Some("vmap"), _) | (XmlSection::Object, Some("ovmap"), _) => {
+                                values.push((key, xml_to_attribute_value(&tag, value)));
+                            }
+                            (XmlSection::Event, None, "id") | (XmlSection::Object, None, "id") => id = value,
+                            (XmlSection::Event, None, "activity") => activity = value,
+                            (XmlSection::Event, None, "timestamp") => {
+                                timestamp = Some(parse_timestamp(&value).ok_or_else(|| {
+                                    format!("Event {} has an unparsable timestamp: {}", id, value)
+                                })?);
+                            }
+                            (XmlSection::Object, None, "type") => object_type = value,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            Event::End(ref e) => match e.name().as_ref() {
+                b"list" => current_list = None,
+                b"global" => section = XmlSection::None,
+                b"event" => {
+                    let timestamp = timestamp.take().ok_or_else(|| format!("Event {} has no timestamp", id))?;
+                    log.events.push(LegacyEvent {
+                        id: std::mem::take(&mut id),
+                        activity: std::mem::take(&mut activity),
+                        timestamp,
+                        omap: std::mem::take(&mut omap),
+                        vmap: std::mem::take(&mut values),
+                    });
+                    section = XmlSection::None;
+                }
+                b"object" => {
+                    log.objects.push(LegacyObject {
+                        id: std::mem::take(&mut id),
+                        object_type: std::mem::take(&mut object_type),
+                        ovmap: std::mem::take(&mut values),
+                    });
+                    section = XmlSection::None;
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(upgrade_to_ocel2(log))
+}
+
+// ---------------------------------------------------------------------------
+// Upgrade
+// ---------------------------------------------------------------------------
+
+fn attribute_type_name(value: &OCELAttributeValue) -> Option<&'static str> {
+    match value {
+        OCELAttributeValue::Time(_) => Some("time"),
+        OCELAttributeValue::Integer(_) => Some("integer"),
+        OCELAttributeValue::Float(_) => Some("float"),
+        OCELAttributeValue::Boolean(_) => Some("boolean"),
+        OCELAttributeValue::String(_) => Some("string"),
+        OCELAttributeValue::Null => None,
+    }
+}
+
+/// Collects the attribute types seen per event/object type, in first-seen order.
+///
+/// Integers mixed with floats widen to `float`; any other mix falls back to `string`.
+#[derive(Default)]
+struct TypeDeclarations {
+    order: Vec<String>,
+    attributes: HashMap<String, Vec<(String, &'static str)>>,
+}
+
+impl TypeDeclarations {
+    fn declare(&mut self, type_name: &str) {
+        if !self.attributes.contains_key(type_name) {
+            self.order.push(type_name.to_string());
+            self.attributes.insert(type_name.to_string(), Vec::new());
+        }
+    }
+
+    fn observe(&mut self, type_name: &str, attribute: &str, value: &OCELAttributeValue) {
+        self.declare(type_name);
+        let Some(value_type) = attribute_type_name(value) else {
+            return;
+        };
+        let attributes = self.attributes.get_mut(type_name).unwrap();
+        match attributes.iter_mut().find(|(name, _)| name == attribute) {
+            Some((_, known)) if *known == value_type => {}
+            Some((_, known)) => {
+                *known = match (*known, value_type) {
+                    ("integer", "float") | ("float", "integer") => "float",
+                    _ => "string",
+                };
+            }
+            None => attributes.push((attribute.to_string(), value_type)),
+        }
+    }
+
+    fn into_types(mut self) -> Vec<OCELType> {
+        self.order
+            .into_iter()
+            .map(|name| {
+                let attributes = self
+                    .attributes
+                    .remove(&name)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(name, value_type)| OCELTypeAttribute { name, value_type: value_type.to_string() })
+                    .collect();
+                OCELType { name, attributes }
+            })
+            .collect()
+    }
+}
+
+// Values whose declared type was widened have to follow, otherwise the
+// attribute would not match its OCELTypeAttribute
+fn coerce_value(value: OCELAttributeValue, declared: &str) -> OCELAttributeValue {
+    match (declared, value) {
+        ("float", OCELAttributeValue::Integer(i)) => OCELAttributeValue::Float(i as f64),
+        ("string", OCELAttributeValue::Time(dt)) => OCELAttributeValue::String(dt.to_rfc3339()),
+        ("string", OCELAttributeValue::Integer(i)) => OCELAttributeValue::String(i.to_string()),
+        ("string", OCELAttributeValue::Float(f)) => OCELAttributeValue::String(f.to_string()),
+        ("string", OCELAttributeValue::Boolean(b)) => OCELAttributeValue::String(b.to_string()),
+        (_, value) => value,
+    }
+}
+
+fn declared_type<'a>(types: &'a [OCELType], type_name: &str, attribute: &str) -> Option<&'a str> {
+    types
+        .iter()
+        .find(|t| t.name == type_name)?
+        .attributes
+        .iter()
+        .find(|a| a.name == attribute)
+        .map(|a| a.value_type.as_str())
+}
+
+pub(crate) fn upgrade_to_ocel2(log: LegacyLog) -> OCEL {
+    let mut event_declarations = TypeDeclarations::default();
+    let mut object_declarations = TypeDeclarations::default();
+
+    for object_type in &log.declared_object_types {
+        object_declarations.declare(object_type);
+    }
+    for event in &log.events {
+        event_declarations.declare(&event.activity);
+        for (name, value) in &event.vmap {
+            event_declarations.observe(&event.activity, name, value);
+        }
+    }
+    for object in &log.objects {
+        object_declarations.declare(&object.object_type);
+        for (name, value) in &object.ovmap {
+            object_declarations.observe(&object.object_type, name, value);
+        }
+    }
+
+    let event_types = event_declarations.into_types();
+    let object_types = object_declarations.into_types();
+
+    let events = log
+        .events
+        .into_iter()
+        .map(|event| {
+            // omap is a set in OCEL 1.0, but exports occasionally repeat ids
+            let mut seen: HashSet<(String, String)> = HashSet::new();
+            let relationships = event
+                .omap
+                .into_iter()
+                .filter(|relation| seen.insert(relation.clone()))
+                .map(|(object_id, qualifier)| OCELRelationship { object_id, qualifier })
+                .collect();
+            let attributes = event
+                .vmap
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = match declared_type(&event_types, &event.activity, &name) {
+                        Some(declared) => coerce_value(value, declared),
+                        None => value,
+                    };
+                    OCELEventAttribute { name, value }
+                })
+                .collect();
+            OCELEvent { id: event.id, event_type: event.activity, time: event.timestamp, attributes, relationships }
+        })
+        .collect();
+
+    let initial_time = DateTime::parse_from_rfc3339(INITIAL_ATTRIBUTE_TIME).unwrap();
+    let objects = log
+        .objects
+        .into_iter()
+        .map(|object| {
+            let attributes = object
+                .ovmap
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = match declared_type(&object_types, &object.object_type, &name) {
+                        Some(declared) => coerce_value(value, declared),
+                        None => value,
+                    };
+                    OCELObjectAttribute { name, value, time: initial_time }
+                })
+                .collect();
+            OCELObject { id: object.id, object_type: object.object_type, attributes, relationships: Vec::new() }
+        })
+        .collect();
+
+    OCEL { event_types, object_types, events, objects }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(id: &str, ovmap: Vec<(&str, OCELAttributeValue)>) -> LegacyObject {
+        LegacyObject {
+            id: id.to_string(),
+            object_type: "item".to_string(),
+            ovmap: ovmap.into_iter().map(|(name, value)| (name.to_string(), value)).collect(),
+        }
+    }
+
+    #[test]
+    fn mixed_attribute_types_are_widened() {
+        let log = LegacyLog {
+            declared_object_types: vec!["item".to_string()],
+            events: Vec::new(),
+            objects: vec![
+                object("i1", vec![("price", OCELAttributeValue::Integer(3)), ("size", OCELAttributeValue::Integer(1))]),
+                object("i2", vec![("price", OCELAttributeValue::Float(2.5)), ("size", OCELAttributeValue::Boolean(true))]),
+            ],
+        };
+        let ocel = upgrade_to_ocel2(log);
+
+        let declared: Vec<(&str, &str)> = ocel.object_types[0]
+            .attributes
+            .iter()
+            .map(|attribute| (attribute.name.as_str(), attribute.value_type.as_str()))
+            .collect();
+        assert_eq!(declared, vec![("price", "float"), ("size", "string")]);
+
+        // The values follow their widened type
+        let values: Vec<&OCELAttributeValue> = ocel.objects[0].attributes.iter().map(|attribute| &attribute.value).collect();
+        assert_eq!(values, vec![&OCELAttributeValue::Float(3.0), &OCELAttributeValue::String("1".to_string())]);
+        assert_eq!(ocel.objects[1].attributes[1].value, OCELAttributeValue::String("true".to_string()));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::format_conversion::{from_json_value, json_to_dfg, json_to_process_forest, process_forest_to_json, json_to_cost_to_add_edges};
-use crate::types::{APIResponse, CutSelectedAPIRequest, ModifyNodeAPIRequest, CutSuggestion, CutSuggestionsList, OCEL, ProcessForest, TreeNode, OCPTWithMetrics, EdgeModification};
-use serde::Deserialize;
+use crate::types::{APIResponse, CutSelectedAPIRequest, ModifyNodeAPIRequest, CutSuggestion, CutSuggestionsList, OCEL, ProcessForest, TreeNode, ObjectTrace, OCPTWithMetrics, EdgeModification, EdgeWeight, NoiseFilter};
+use serde::{Deserialize, Serialize};
 use simplelog::*;
 use std::collections::{HashMap, HashSet};
 use std::env;
@@ -8,22 +8,43 @@ use std::fs as stdfs;
 use std::fs::File;
 use std::io::Write;
 use std::sync::Arc;
-use uuid::Uuid;
 mod add_self_loops;
 mod build_relations_fns;
 mod conformance_format;
 mod divergence_free_dfg;
+mod fall_throughs;
+mod event_log_index;
+mod flat_log_import;
 mod format_conversion;
 mod get_dfg_by_object_type;
 mod interaction_patterns;
+mod legacy_ocel_import;
+mod node_ids;
+mod o2o_propagation;
+mod ocel_filter;
+mod ocel_import;
+mod ocel_projection;
+mod ocel_statistics;
+mod qualifier_split;
+mod ocel_stream;
+mod ocel_validation;
 mod start_cuts;
 mod start_cuts_opti_v1;
 mod start_cuts_opti_v2;
 mod types;
+mod variants;
 mod conformance_types;
 mod modify_node_helper_fns;
 use log::info;
 use add_self_loops::add_self_loops;
+use cut_finders::CutStrategies;
+use event_log_index::{EventLogIndex, TieBreak};
+use interaction_patterns::PatternThresholds;
+use ocel_stream::CompactOCEL;
+use o2o_propagation::O2OPropagation;
+use ocel_filter::OCELFilter;
+use ocel_projection::LogProjection;
+use qualifier_split::QualifierSplit;
 mod best_exclusive_cut;
 mod best_parallel_cut;
 mod best_parallel_cut_exhaustive;
@@ -37,6 +58,7 @@ mod cost_to_add;
 mod cost_to_add_curve_fitting;
 mod cost_to_add_curve_fitting1;
 mod cost_to_cut;
+mod cut_finders;
 mod good_cuts;
 use crate::cost_to_add::cost_of_adding_edge;
 use axum::extract::{DefaultBodyLimit, Json as AxumJson, Multipart, Path, Query};
@@ -73,9 +95,102 @@ async fn hello() -> Json<Value> {
     Json(json)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Params {
     n: Option<f64>,
+    /// Ordering of events with equal timestamps: `event_id` (default), `file_order` or `activity_order`
+    tie_break: Option<String>,
+    /// Comma separated activities, used with `tie_break=activity_order`
+    activity_order: Option<String>,
+    /// DFG edge weight the cuts are computed on: `percentage` (default), `count` or `objects`
+    weight: Option<String>,
+    /// How `n` filters infrequent edges: `global` (default) or `activity`
+    noise: Option<String>,
+    /// Comma separated object types to discover on (default: all)
+    include_object_types: Option<String>,
+    /// Comma separated object types to leave out
+    exclude_object_types: Option<String>,
+    /// Comma separated activities to discover on (default: all)
+    include_activities: Option<String>,
+    /// Comma separated activities to leave out
+    exclude_activities: Option<String>,
+    /// JSON time window, attribute and lifecycle filter, see [`OCELFilter`]
+    filter: Option<String>,
+    /// Comma separated `inheriting_type:source_type` pairs, see [`O2OPropagation`]
+    o2o: Option<String>,
+    /// Relates events to the O2O-linked objects of types their activity has no E2O relationship
+    /// to (default: false), see [`interaction_patterns::relate_o2o_linked_objects`]
+    o2o_patterns: Option<bool>,
+    /// Comma separated object types to split by E2O qualifier, see [`QualifierSplit`]
+    split_qualifiers: Option<String>,
+    /// Share of events (0 to 1) an activity/object type pair must diverge in to count as divergent
+    divergent_threshold: Option<f64>,
+    /// Share of events (0 to 1) with several objects of the type for the pair to count as convergent
+    convergent_threshold: Option<f64>,
+    /// Share of events (0 to 1) without an object of the type for the pair to count as deficient
+    deficient_threshold: Option<f64>,
+    /// Cut finder of exclusive cuts: `best_exclusive_cut` (default) or `best_sequence_cut_v2`, see `GET /cut-finders`
+    exclusive_cut: Option<String>,
+    /// Cut finder of sequence cuts: `best_sequence_cut` (default) or `good_cuts`
+    sequence_cut: Option<String>,
+    /// Cut finder of parallel cuts: `best_parallel_cut_v4` (default), `best_parallel_cut`, `_v2`, `_v3` or `_exhaustive`
+    parallel_cut: Option<String>,
+    /// Cut finder of redo cuts: `best_redo_cut` (default)
+    redo_cut: Option<String>,
+    /// Seed of the randomised cut finders (default: 0)
+    seed: Option<u64>,
+}
+
+/// Discovery settings parsed from [`Params`]
+#[derive(Debug, Default)]
+struct DiscoveryOptions {
+    n: Option<f64>,
+    noise_filter: NoiseFilter,
+    tie_break: TieBreak,
+    edge_weight: EdgeWeight,
+    projection: LogProjection,
+    filter: OCELFilter,
+    o2o: O2OPropagation,
+    o2o_patterns: bool,
+    qualifier_split: QualifierSplit,
+    pattern_thresholds: PatternThresholds,
+    cut_strategies: CutStrategies,
+    /// The parameters themselves, echoed so that later steps prepare the log the same way
+    params: Params,
+}
+
+impl DiscoveryOptions {
+    fn from_params(params: Params) -> Result<Self, String> {
+        Ok(DiscoveryOptions {
+            n: params.n,
+            noise_filter: NoiseFilter::from_param(params.noise.as_deref())?,
+            tie_break: TieBreak::from_params(params.tie_break.as_deref(), params.activity_order.as_deref())?,
+            edge_weight: EdgeWeight::from_param(params.weight.as_deref())?,
+            projection: LogProjection::from_params(
+                params.include_object_types.as_deref(),
+                params.exclude_object_types.as_deref(),
+                params.include_activities.as_deref(),
+                params.exclude_activities.as_deref(),
+            ),
+            filter: OCELFilter::from_param(params.filter.as_deref())?,
+            o2o: O2OPropagation::from_param(params.o2o.as_deref())?,
+            o2o_patterns: params.o2o_patterns.unwrap_or(false),
+            qualifier_split: QualifierSplit::from_param(params.split_qualifiers.as_deref()),
+            pattern_thresholds: PatternThresholds::from_params(
+                params.divergent_threshold,
+                params.convergent_threshold,
+                params.deficient_threshold,
+            )?,
+            cut_strategies: CutStrategies::from_params(
+                params.exclusive_cut.as_deref(),
+                params.sequence_cut.as_deref(),
+                params.parallel_cut.as_deref(),
+                params.redo_cut.as_deref(),
+                params.seed,
+            )?,
+            params,
+        })
+    }
 }
 
 
@@ -123,10 +238,11 @@ async fn upload_handler(mut multipart: Multipart) -> Result<Json<Value>, StatusC
             })));
         }
 
-        if !filename.ends_with(".json") && !filename.ends_with(".jsonocel") {
+        let is_flat_log = flat_log_import::is_flat_log_file(&filename);
+        if ocel_import::format_from_extension(std::path::Path::new(&filename)).is_none() && !is_flat_log {
             return Ok(Json(serde_json::json!({
                 "success": false,
-                "message": "Only JSON and JSONOCEL files are allowed"
+                "message": "Only JSON, JSONOCEL, XMLOCEL, XML, SQLite, CSV and XES files are allowed"
             })));
         }
 
@@ -184,6 +300,22 @@ async fn upload_handler(mut multipart: Multipart) -> Result<Json<Value>, StatusC
             })));
         }
 
+        // Check the content actually is an OCEL log before publishing it.
+        // Flat logs are only checked once they are converted via /import-flat.
+        let format = match ocel_import::sniff_format(std::path::Path::new(&tmp_path)) {
+            _ if is_flat_log => "flat",
+            Some(format) => format.as_str(),
+            None => {
+                println!("Uploaded file is not a recognized OCEL format: {}", filename);
+                let _ = tokiofs::remove_file(&tmp_path).await;
+                return Ok(Json(serde_json::json!({
+                    "success": false,
+                    "message": "Uploaded file is not a JSON, XML or SQLite OCEL log (2.0 or 1.0)",
+                    "received_bytes": total_bytes
+                })));
+            }
+        };
+
         // Publish the file only after fully written.
         let _ = tokiofs::remove_file(&file_path).await;
         if let Err(e) = tokiofs::rename(&tmp_path, &file_path).await {
@@ -211,6 +343,7 @@ async fn upload_handler(mut multipart: Multipart) -> Result<Json<Value>, StatusC
             "message": "File uploaded successfully!",
             "filename": filename,
             "path": file_path,
+            "format": format,
             "received_bytes": total_bytes,
             "saved_bytes": saved_size
         })));
@@ -222,42 +355,496 @@ async fn upload_handler(mut multipart: Multipart) -> Result<Json<Value>, StatusC
     })))
 }
 
+#[derive(Deserialize)]
+pub struct ImportFlatParams {
+    /// Replace a log already stored under the same name (default: false)
+    #[serde(default)]
+    overwrite: bool,
+}
+
+// Handler for POST /import-flat/:file_name
+// Converts an uploaded CSV/XES event table into data/{stem}.json using the posted
+// column mapping, so that GET /{stem} can discover a model from it.
+async fn import_flat_handler(
+    Path(file_name): Path<String>,
+    Query(import_params): Query<ImportFlatParams>,
+    AxumJson(mapping): AxumJson<flat_log_import::FlatLogMapping>,
+) -> Json<Value> {
+    if file_name.contains("..") || file_name.contains('/') || file_name.contains('\\') {
+        return Json(serde_json::json!({
+            "success": false,
+            "message": "Invalid filename"
+        }));
+    }
+    if !flat_log_import::is_flat_log_file(&file_name) {
+        return Json(serde_json::json!({
+            "success": false,
+            "message": "Only CSV and XES files can be imported with a column mapping"
+        }));
+    }
+
+    let lower = file_name.to_lowercase();
+    let stem_len = [".xes.gz", ".xes", ".csv"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map(|ext| file_name.len() - ext.len())
+        .unwrap_or(file_name.len());
+    let stem = &file_name[..stem_len];
+    let target_path = format!("data/{}.json", stem);
+
+    // GET /{stem} would load the existing log or the converted one, never both
+    if let Some(existing) = ocel_import::resolve_ocel_path(stem).filter(|_| !import_params.overwrite) {
+        return Json(serde_json::json!({
+            "success": false,
+            "message": format!("{} already exists; import with overwrite=true to replace it", existing.display()),
+            "file_name": stem,
+            "path": target_path
+        }));
+    }
+
+    let source_path = format!("data/{}", file_name);
+    let ocel = match flat_log_import::import_flat_log(std::path::Path::new(&source_path), &mapping) {
+        Ok(ocel) => ocel,
+        Err(message) => {
+            println!("{}", message);
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+
+    let write_result = File::create(&target_path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| serde_json::to_writer(std::io::BufWriter::new(file), &ocel).map_err(|e| e.to_string()));
+    if let Err(e) = write_result {
+        println!("Failed writing converted log {}: {}", target_path, e);
+        return Json(serde_json::json!({
+            "success": false,
+            "message": "Failed writing converted OCEL log"
+        }));
+    }
+
+    println!("Converted {} into {} ({} events, {} objects)", source_path, target_path, ocel.events.len(), ocel.objects.len());
+
+    Json(serde_json::json!({
+        "success": true,
+        "message": "Flat log converted to OCEL",
+        "file_name": stem,
+        "path": target_path,
+        "events": ocel.events.len(),
+        "objects": ocel.objects.len(),
+        "object_types": ocel.object_types.iter().map(|t| t.name.clone()).collect::<Vec<_>>()
+    }))
+}
+
+// Handler for GET /validate/:file_name
+// Reports the problems in data/{file_name}.* that would make discovery panic or drop data.
+async fn validate_handler(Path(file_name): Path<String>) -> Json<Value> {
+    if file_name.contains("..") || file_name.contains('/') || file_name.contains('\\') {
+        return Json(serde_json::json!({
+            "success": false,
+            "message": "Invalid filename"
+        }));
+    }
+
+    match ocel_validation::validate_ocel_file(&file_name) {
+        Ok(report) => {
+            println!("Validated {}: {} issue(s)", file_name, report.issue_count());
+            Json(serde_json::json!({
+                "success": true,
+                "valid": report.is_valid(),
+                "issues": report.issue_count(),
+                "report": report
+            }))
+        }
+        Err(message) => {
+            println!("{}", message);
+            Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }))
+        }
+    }
+}
+
 async fn get_initial_response(
     Path(file_name): Path<String>,
     Query(params): Query<Params>,
 ) -> Json<Value> {
-    process_response(file_name, params.n).await
+    match DiscoveryOptions::from_params(params) {
+        Ok(options) => process_response(file_name, options).await,
+        Err(message) => Json(serde_json::json!({
+            "success": false,
+            "message": message
+        })),
+    }
+}
+
+// Handler for GET /interaction-patterns/:file_name
+// Takes the same query parameters as discovery; the thresholds decide which patterns hold
+async fn interaction_patterns_handler(
+    Path(file_name): Path<String>,
+    Query(params): Query<Params>,
+) -> Json<Value> {
+    let (log_index, options) = match prepare_log_index(&file_name, params) {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+
+    let (patterns, support) =
+        interaction_patterns::get_interaction_patterns_with_support(&log_index, &options.pattern_thresholds);
+    let matrix = interaction_patterns::pattern_matrix(&patterns);
+    let (div, con, rel, defi, all_activities, all_object_types) = patterns;
+    println!("Interaction patterns of {}: {} activities, {} object types", file_name, all_activities.len(), all_object_types.len());
+
+    Json(serde_json::json!({
+        "success": true,
+        "activities": all_activities,
+        "object_types": all_object_types,
+        "matrix": matrix,
+        "divergent": div,
+        "convergent": con,
+        "related": rel,
+        "deficient": defi,
+        "support": support
+    }))
+}
+
+// Handler for GET /object-type-dfgs/:file_name
+// Returns the DFG of every object type with its start and end activities and edge waiting
+// times, keyed by object type
+async fn object_type_dfgs_handler(
+    Path(file_name): Path<String>,
+    Query(params): Query<Params>,
+) -> Json<Value> {
+    let (log_index, options) = match prepare_log_index(&file_name, params) {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+
+    let ((div, ..), _) =
+        interaction_patterns::get_interaction_patterns_with_support(&log_index, &options.pattern_thresholds);
+    let dfg_sets = get_dfg_by_object_type::get_dfg_by_object_type(&log_index, &div);
+    println!("Object type DFGs of {}: {} object types", file_name, dfg_sets.len());
+
+    let object_types: std::collections::BTreeMap<String, Value> = dfg_sets
+        .into_iter()
+        .map(|(otype, object_type_dfg)| {
+            let mut start_activities: Vec<String> = object_type_dfg.start_activities.into_iter().collect();
+            start_activities.sort();
+            let mut end_activities: Vec<String> = object_type_dfg.end_activities.into_iter().collect();
+            end_activities.sort();
+            let value = serde_json::json!({
+                "dfg": format_conversion::dfg_to_json_with_waiting_times(&object_type_dfg.dfg, &object_type_dfg.waiting_times),
+                "start_activities": start_activities,
+                "end_activities": end_activities
+            });
+            (otype, value)
+        })
+        .collect();
+
+    Json(serde_json::json!({
+        "success": true,
+        "object_types": object_types
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct VariantParams {
+    /// Number of most frequent variants to return per object type (default: all)
+    top: Option<usize>,
+}
+
+// Handler for GET /variants/:file_name
+// Groups the object traces of the prepared log into variants per object type
+async fn variants_handler(
+    Path(file_name): Path<String>,
+    Query(params): Query<Params>,
+    Query(variant_params): Query<VariantParams>,
+) -> Json<Value> {
+    let (log_index, options) = match prepare_log_index(&file_name, params) {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+
+    let ((div, ..), _) =
+        interaction_patterns::get_interaction_patterns_with_support(&log_index, &options.pattern_thresholds);
+    let traces = add_self_loops::get_traces_from_index(&log_index, &div);
+    let mut variants = variants::get_variants(&traces);
+    println!("Variants of {}: {} traces over {} object types", file_name, traces.len(), variants.len());
+    if let Some(top) = variant_params.top {
+        for object_type_variants in &mut variants {
+            object_type_variants.variants.truncate(top);
+        }
+    }
+
+    Json(serde_json::json!({
+        "success": true,
+        "object_types": variants
+    }))
+}
+
+// Handler for GET /statistics/:file_name
+// Descriptive statistics per object type of the log after the filters and projection of the
+// query parameters
+async fn statistics_handler(
+    Path(file_name): Path<String>,
+    Query(params): Query<Params>,
+) -> Json<Value> {
+    if file_name.contains("..") || file_name.contains('/') || file_name.contains('\\') {
+        return Json(serde_json::json!({
+            "success": false,
+            "message": "Invalid filename"
+        }));
+    }
+    let prepared = DiscoveryOptions::from_params(params)
+        .and_then(|options| Ok((prepare_log(&file_name, &options)?.0, options.tie_break)));
+    let (compact, tie_break) = match prepared {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+
+    let statistics = ocel_statistics::get_statistics(compact, &tie_break);
+    println!("Statistics of {}: {} object types", file_name, statistics.object_types.len());
+
+    Json(serde_json::json!({
+        "success": true,
+        "statistics": statistics
+    }))
+}
+
+// Handler for GET /cut-finders
+// Lists the cut finders discovery requests can choose per cut type, see `Params`
+async fn cut_finders_handler() -> Json<Value> {
+    let defaults = CutStrategies::default().finders().map(|finder| finder.name());
+    let cut_finders: Vec<Value> = cut_finders::CUT_FINDERS
+        .iter()
+        .map(|finder| serde_json::json!({
+            "name": finder.name(),
+            "cut_type": finder.cut_type(),
+            "default": defaults.contains(&finder.name())
+        }))
+        .collect();
+
+    Json(serde_json::json!({
+        "success": true,
+        "cut_finders": cut_finders
+    }))
+}
+
+// Parses the discovery parameters and builds the relation table of the prepared log
+fn prepare_log_index(file_name: &str, params: Params) -> Result<(EventLogIndex, DiscoveryOptions), String> {
+    if file_name.contains("..") || file_name.contains('/') || file_name.contains('\\') {
+        return Err("Invalid filename".to_string());
+    }
+    let options = DiscoveryOptions::from_params(params)?;
+    let (compact, _remove_list) = prepare_log(file_name, &options)?;
+    let log_index = EventLogIndex::from_compact(compact, &options.tie_break);
+    Ok((log_index, options))
 }
 
 async fn get_initial_response_default() -> Json<Value> {
-    process_response("order-management".to_string(), None).await
+    process_response("order-management".to_string(), DiscoveryOptions::default()).await
 }
 
-async fn process_response(file_name_input: String, n_val: Option<f64>) -> Json<Value> {
+/// Loads a log and applies the O2O propagation, filter, projection and qualifier split of
+/// `options`. Returns the prepared log and the activities the projection dropped.
+///
+/// Without filter, O2O propagation or qualifier split the log is streamed straight into the
+/// compact model and projected there. Those transforms need the full [`OCEL`]; it is then
+/// loaded with only the attributes the filter reads and compacted once they are done.
+/// O2O-linked objects are related last, on the prepared log.
+fn prepare_log(file_name: &str, options: &DiscoveryOptions) -> Result<(CompactOCEL, Vec<String>), String> {
+    let DiscoveryOptions { filter, projection, o2o, qualifier_split, .. } = options;
+
+    let (mut compact, remove_list) = if filter.is_empty() && o2o.is_empty() && qualifier_split.is_empty() {
+        let compact = ocel_import::load_compact_ocel(file_name).map_err(|message| import_error(file_name, message))?;
+        let remove_list = projection.removed_activities(
+            compact.events.iter().map(|event| compact.event_types[event.event_type as usize].as_str()),
+        );
+        let compact = projection.apply_compact(compact);
+        if !projection.is_empty() {
+            println!("Projected log with {:?}: {} events, {} objects left", projection, compact.events.len(), compact.objects.len());
+            if compact.events.is_empty() {
+                return Err("No events left after applying the object type and activity selection".to_string());
+            }
+        }
+        (compact, remove_list)
+    } else {
+        transform_log(file_name, options)?
+    };
 
-    println!("Starting...");
-    let n_threshold = n_val.unwrap_or(0.00);
-    println!("Using query param n: {}", n_threshold);
+    if options.o2o_patterns {
+        let added = interaction_patterns::relate_o2o_linked_objects(&mut compact);
+        println!("Related events to O2O-linked objects: {} relationship(s) added", added);
+    }
+
+    Ok((compact, remove_list))
+}
 
+// Relation table and divergent object types of a log prepared the way its discovery was:
+// filtered, projected, propagated and split alike
+fn prepare_divergence(file_name: &str, options: &DiscoveryOptions) -> Result<(EventLogIndex, HashMap<String, Vec<String>>), String> {
+    let (compact, _remove_list) = prepare_log(file_name, options)?;
+    let log_index = EventLogIndex::from_compact(compact, &options.tie_break);
+    let ((divergent_objects, ..), _support) =
+        interaction_patterns::get_interaction_patterns_with_support(&log_index, &options.pattern_thresholds);
+    Ok((log_index, divergent_objects))
+}
+
+fn traces_path(file_name: &str) -> String {
+    format!("conformance_files/{}-traces.json", file_name)
+}
+
+/// Traces of the last discovery of a log, with the parameters it ran with
+#[derive(Serialize, Deserialize)]
+struct SavedTraces {
+    discovery_params: Params,
+    traces: Vec<ObjectTrace>,
+}
+
+// Saves the traces of a discovery so that cut selections need not prepare the log again.
+// A failure only costs that, so it is logged and discovery goes on.
+fn save_traces(file_name: &str, params: &Params, traces: &[ObjectTrace]) {
+    let saved = serde_json::json!({ "discovery_params": params, "traces": traces });
+    let path = traces_path(file_name);
+    let written = stdfs::create_dir_all("conformance_files")
+        .and_then(|_| stdfs::write(&path, saved.to_string()));
+    if let Err(e) = written {
+        println!("Could not save the traces to {}: {}", path, e);
+    }
+}
+
+// The saved traces of `file_name`, if its last discovery ran with `params`
+fn load_traces(file_name: &str, params: &Params) -> Option<Vec<ObjectTrace>> {
+    let content = stdfs::read_to_string(traces_path(file_name)).ok()?;
+    let saved: SavedTraces = serde_json::from_str(&content).ok()?;
+    (saved.discovery_params == *params).then_some(saved.traces)
+}
+
+// Logs an import error and points to the validation report of the log
+fn import_error(file_name: &str, message: String) -> String {
+    println!("{}", message);
+    format!("{} (see /validate/{} for details)", message, file_name)
+}
+
+// The part of `prepare_log` that works on the full log: O2O propagation, filter, projection
+// and qualifier split
+fn transform_log(file_name: &str, options: &DiscoveryOptions) -> Result<(CompactOCEL, Vec<String>), String> {
+    let DiscoveryOptions { filter, projection, o2o, qualifier_split, .. } = options;
+
+    let mut ocel: OCEL = ocel_import::load_ocel_with_attributes(file_name, &filter.attribute_names())
+        .map_err(|message| import_error(file_name, message))?;
+
+    // Propagate first, so the filters and the projection see the inherited events
+    if !o2o.is_empty() {
+        let added;
+        (ocel, added) = o2o.apply(ocel);
+        println!("Propagated events along O2O links {:?}: {} relationship(s) added", o2o.rules, added);
+    }
+
+    if !filter.is_empty() {
+        let events_before = ocel.events.len();
+        let objects_before = ocel.objects.len();
+        ocel = filter.apply(ocel);
+        println!(
+            "Filtered log with {:?}: {}/{} events, {}/{} objects left",
+            filter,
+            ocel.events.len(),
+            events_before,
+            ocel.objects.len(),
+            objects_before
+        );
+        if ocel.events.is_empty() {
+            return Err("No events left after applying the filter".to_string());
+        }
+    }
+
+    // Activities dropped by the projection are what used to be the hard-coded remove list
+    let remove_list = projection.removed_activities(ocel.events.iter().map(|event| event.event_type.as_str()));
+    let ocel = projection.apply(ocel);
+    if !projection.is_empty() {
+        println!("Projected log with {:?}: {} events, {} objects left", projection, ocel.events.len(), ocel.objects.len());
+        if ocel.events.is_empty() {
+            return Err("No events left after applying the object type and activity selection".to_string());
+        }
+    }
+
+    // Split last, so the filters and the projection use the object types of the log
+    let ocel = qualifier_split.apply(ocel);
+    if !qualifier_split.is_empty() {
+        println!("Split object types {:?} by qualifier: {} objects", qualifier_split.object_types, ocel.objects.len());
+    }
+
+    let mut compact = CompactOCEL::new();
+    ocel_stream::feed_ocel(ocel, &mut compact);
+    Ok((compact, remove_list))
+}
+
+async fn process_response(file_name_input: String, options: DiscoveryOptions) -> Json<Value> {
     // Changed to use OCEL 2.0 format
     let file_name = if file_name_input.is_empty() {
         "order-management"
     } else {
         &file_name_input
     };
-    
-    let file_path = format!("data/{}.json", file_name);
 
-    let file_content = stdfs::read_to_string(&file_path).unwrap();
-    let ocel: OCEL = serde_json::from_str(&file_content).unwrap();
+    let (compact, remove_list) = match prepare_log(file_name, &options) {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+
+    let DiscoveryOptions {
+        n: n_val,
+        noise_filter,
+        tie_break,
+        edge_weight,
+        filter,
+        pattern_thresholds,
+        cut_strategies,
+        params,
+        ..
+    } = options;
+
+    println!("Starting...");
+    let n_threshold = n_val.unwrap_or(0.00);
+    println!("Using query param n: {} ({:?} noise filter)", n_threshold, noise_filter);
+    println!("Ordering events with equal timestamps by: {:?}", tie_break);
+    println!("Using DFG edge weight: {:?}", edge_weight);
 
-    let relations = build_relations_fns::build_relations(&ocel.events, &ocel.objects);
-    // println!("size of relations: {}", relations.len());
+    conformance_format::build_ocel_format_for_conformance(&compact, file_name);
 
-    conformance_format::build_ocel_format_for_conformance(&ocel.events, &ocel.objects, &file_name);
+    let log_index = EventLogIndex::from_compact(compact, &tie_break);
+    // println!("size of relations: {}", log_index.relations.len());
 
-    let (div, con, rel, defi, all_activities, all_object_types) =
-        interaction_patterns::get_interaction_patterns(&relations, &ocel);
+    let ((div, con, rel, defi, all_activities, all_object_types), interaction_pattern_support) =
+        interaction_patterns::get_interaction_patterns_with_support(&log_index, &pattern_thresholds);
 
     conformance_format::saveInteractionPatterns(&div, &con, &rel, &defi, &file_name);
 
@@ -270,26 +857,33 @@ async fn process_response(file_name_input: String, n_val: Option<f64>) -> Json<V
 
 
     // Get DFGs by object type
-    let dfg_sets = get_dfg_by_object_type::get_dfg_by_object_type(&relations, &div);
+    let dfg_sets = get_dfg_by_object_type::get_dfg_by_object_type(&log_index, &div);
 
     // print first 5 relations tuple
     println!("First 5 relations:");
-    for relation in &relations[..5] {
-        println!("{:?}", relation);
+    for relation in log_index.relations.iter().take(5) {
+        println!(
+            "({:?}, {:?}, {:?}, {:?}, {:?})",
+            log_index.event_id(relation.event),
+            log_index.activity(relation.activity),
+            relation.time.to_rfc3339(),
+            log_index.object_id(relation.object),
+            log_index.object_type(relation.object_type)
+        );
     }
     //print div
     println!("Divergent: {:?}", div);
 
     // Print information about DFGs by object type
     println!("DFGs by object type:");
-    for (otype, (dfg_otype, start_acts_otype, end_acts_otype)) in &dfg_sets {
+    for (otype, object_type_dfg) in &dfg_sets {
         println!("\nObject Type: {}", otype);
-        println!("Number of edges in DFG: {}", dfg_otype.len());
-        println!("Start activities: {:?}", start_acts_otype);
-        println!("End activities: {:?}", end_acts_otype);
-        if !dfg_otype.is_empty() {
+        println!("Number of edges in DFG: {}", object_type_dfg.dfg.len());
+        println!("Start activities: {:?}", object_type_dfg.start_activities);
+        println!("End activities: {:?}", object_type_dfg.end_activities);
+        if !object_type_dfg.dfg.is_empty() {
             println!("DFG edges:");
-            print_dfg(dfg_otype);
+            print_dfg(&object_type_dfg.dfg);
         }
     }
 
@@ -302,26 +896,29 @@ async fn process_response(file_name_input: String, n_val: Option<f64>) -> Json<V
     // log_sorted_map("Relational", &rel);
     // log_sorted_map("Deficiency", &defi);
 
-    let (mut dfg, start_acts, end_acts) =
-        divergence_free_dfg::get_divergence_free_graph_v2(&relations, &div);
+    let oc_dfg = divergence_free_dfg::get_oc_dfg(&log_index, &div);
+    let (dfg_weights, start_acts, end_acts) = oc_dfg.merged();
+    let mut dfg = divergence_free_dfg::weighted_dfg(&dfg_weights, edge_weight);
 
     println!("created DFG!");
+    let mut noise_removed_edges = Vec::new();
     if n_threshold > 0.0 {
-        dfg = noise_reduction(dfg, n_threshold);
+        (dfg, noise_removed_edges) = match noise_filter {
+            NoiseFilter::Global => noise_reduction(dfg, n_threshold),
+            NoiseFilter::PerActivity => noise_reduction_per_activity(dfg, n_threshold),
+        };
+        println!("Removed {} edge(s) as noise: {:?}", noise_removed_edges.len(), noise_removed_edges);
         println!("DFG after noise reduction:");
     }
 
     print_dfg(&dfg);
 
-    let remove_list = vec![];
-    // let remove_list = vec!["failed delivery".to_string(),"payment reminder".to_string()];
-    // let remove_list = vec!["reopened".to_string()];
     let filtered_dfg = filter_dfg(&dfg, &remove_list);
     let filtered_activities = filter_activities(&all_activities, &remove_list);
 
     
 
-    let json_dfg: Value = format_conversion::dfg_to_json(&dfg);
+    let json_dfg: Value = format_conversion::dfg_to_json_with_oc_dfg(&dfg, &oc_dfg);
 
     // Save to file
     let dfs_path = format!("ddfg-diagrams/ddfg_{}.json", file_name);
@@ -333,11 +930,15 @@ async fn process_response(file_name_input: String, n_val: Option<f64>) -> Json<V
 
     // In case of filtering activities in the begining
     // let process_forest = start_cuts::find_cuts(&filtered_dfg, &filtered_dfg, filtered_activities, &start_acts, &end_acts);
-    let process_forest = start_cuts_opti_v2::find_cuts_start(
+    // Object traces let discovery fall back to the trace-based fall-throughs
+    let traces = add_self_loops::get_traces_from_index(&log_index, &div);
+    save_traces(file_name, &params, &traces);
+    let process_forest = start_cuts_opti_v2::find_cuts_start_with_traces(
         &filtered_dfg,
         &filtered_activities,
         &start_acts,
         &end_acts,
+        &traces,
     );
 
     let mut response:APIResponse = APIResponse {
@@ -354,6 +955,11 @@ async fn process_response(file_name_input: String, n_val: Option<f64>) -> Json<V
         total_edges_removed: Vec::new(),
         edge_modifications: Vec::new(),
         cost_to_add_edges: serde_json::json!({}),
+        noise_removed_edges,
+        filter,
+        interaction_pattern_support,
+        cut_strategies: cut_strategies.names(),
+        discovery_params: params,
         precision: 0.0,
         fitness: 0.0,
         f_score: 0.0,
@@ -378,8 +984,10 @@ async fn process_response(file_name_input: String, n_val: Option<f64>) -> Json<V
             disjoint_activities
         );
         // Get costs to add edges
-        // let cost_to_add_edges = cost_of_adding_edge(&relations, &div, &filtered_dfg);
-        let cost_to_add_edges = cost_to_add_curve_fitting::cost_of_adding_edge(&relations, &div, &filtered_dfg);
+        // let cost_to_add_edges = cost_of_adding_edge(&log_index, &div, &filtered_dfg);
+        let mut cost_to_add_edges = cost_to_add_curve_fitting::cost_of_adding_edge(&log_index, &div, &filtered_dfg);
+        // Compared against the removed edge weights, so both need the same scale
+        divergence_free_dfg::scale_costs_to_weight(&mut cost_to_add_edges, &dfg_weights, edge_weight);
         let json_cost_to_add_edges: Value = format_conversion::cost_to_add_edges_to_json(&cost_to_add_edges);
 
         response.is_perfectly_cut = false;
@@ -389,14 +997,15 @@ async fn process_response(file_name_input: String, n_val: Option<f64>) -> Json<V
             &disjoint_activities,
             &start_acts,
             &end_acts,
-            &cost_to_add_edges
+            &cost_to_add_edges,
+            &cut_strategies,
         );
         response.cut_suggestions_list = cut_suggestions_list;
     } else {
         println!("No disjoint activities found in the OCPT");
 
         // Get the modified OCPT with self-loops added
-        let (modified_ocpt, self_loop_activities) = add_self_loops(&dfg.clone(), &process_forest, file_name);
+        let (modified_ocpt, self_loop_activities) = add_self_loops(&dfg.clone(), &process_forest, &log_index, &div);
         println!("Self-loop activities processed: {:?}", self_loop_activities);
         
         // Update the response with the modified OCPT
@@ -466,14 +1075,9 @@ fn test_conformance(ocpt_data: serde_json::Value) {
 
      // Read OCEL log
     let file_name = "order-management";
-    let file_path = format!("data/{}.json", file_name);
-    
-    println!("Reading OCEL log from: {}", file_path);
-    let file_content = stdfs::read_to_string(&file_path)
-        .expect("Failed to read OCEL file");
-    
-    let ocel: OCEL = serde_json::from_str(&file_content)
-        .expect("Failed to parse OCEL JSON");
+    println!("Reading OCEL log: {}", file_name);
+    let ocel: OCEL = ocel_import::load_ocel(file_name)
+        .expect("Failed to load OCEL log");
     
     println!("✓ OCEL log loaded successfully");
     println!("  - Events: {}", ocel.events.len());
@@ -524,28 +1128,28 @@ async fn all_possible_ocpts() -> Json<Value> {
     // let file_name = "ocel2-p2p";
     // let file_name = "ContainerLogistics";
     // let file_name = "age_of_empires_ocel2";
-    let file_path = format!("data/{}.json", file_name);
-
-    let file_content = stdfs::read_to_string(&file_path).unwrap();
-    let ocel: OCEL = serde_json::from_str(&file_content).unwrap();
+    let ocel: OCEL = ocel_import::load_ocel(file_name).unwrap();
 
-    let relations = build_relations_fns::build_relations(&ocel.events, &ocel.objects);
+    let log_index = build_relations_fns::build_relations(&ocel.events, &ocel.objects, &TieBreak::default());
     let (div, _con, _rel, _defi, all_activities, _all_object_types) =
-        interaction_patterns::get_interaction_patterns(&relations, &ocel);
+        interaction_patterns::get_interaction_patterns(&log_index);
 
-    let (dfg, start_acts, end_acts) =
-        divergence_free_dfg::get_divergence_free_graph_v2(&relations, &div);
+    let (dfg_weights, start_acts, end_acts) =
+        divergence_free_dfg::get_divergence_free_graph_v2(&log_index, &div);
+    let dfg = divergence_free_dfg::weighted_dfg(&dfg_weights, EdgeWeight::default());
 
     let remove_list = vec![];
     let filtered_dfg = filter_dfg(&dfg, &remove_list);
     let filtered_activities = filter_activities(&all_activities, &remove_list);
 
     // Get initial process forest
-    let initial_process_forest = start_cuts_opti_v2::find_cuts_start(
+    let traces = add_self_loops::get_traces_from_index(&log_index, &div);
+    let initial_process_forest = start_cuts_opti_v2::find_cuts_start_with_traces(
         &filtered_dfg,
         &filtered_activities,
         &start_acts,
         &end_acts,
+        &traces,
     );
 
     // Structure to track state for recursion
@@ -559,7 +1163,7 @@ async fn all_possible_ocpts() -> Json<Value> {
         sequence_of_choices: Vec<String>,
     }
 
-    let initial_cost_to_add_edges = cost_of_adding_edge(&relations, &div, &filtered_dfg);
+    let initial_cost_to_add_edges = cost_of_adding_edge(&log_index, &div, &filtered_dfg);
     
     let initial_state = OCPTState {
         ocpt: initial_process_forest.clone(),
@@ -595,7 +1199,8 @@ async fn all_possible_ocpts() -> Json<Value> {
                 &disjoint_activities,
                 &start_acts,
                 &end_acts,
-                &current_state.cost_to_add_edges
+                &current_state.cost_to_add_edges,
+                &CutStrategies::default(),
             );
 
             if cut_suggestions_list.cuts.is_empty() {
@@ -632,6 +1237,8 @@ async fn all_possible_ocpts() -> Json<Value> {
                         cut_suggestion,
                         &start_acts,
                         &end_acts,
+                        &new_state.ocpt,
+                        &traces,
                     );
 
                     // Modify the process forest
@@ -819,6 +1426,35 @@ async fn process_cut_selected(
 ) -> Json<Value> {
     println!("Received cut-selected request: {:?}", payload.cut_selected);
 
+    let cut_strategies = match CutStrategies::from_names(&payload.cut_strategies) {
+        Ok(cut_strategies) => cut_strategies,
+        Err(message) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+
+    // The self-loop step prepares the log the way discovery did
+    let discovery_options = match DiscoveryOptions::from_params(payload.discovery_params) {
+        Ok(discovery_options) => discovery_options,
+        Err(message) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+    // The filter applied is the one of the discovery parameters; the parsed copy echoed back
+    // must not disagree with it
+    if !payload.filter.is_empty() && payload.filter != discovery_options.filter {
+        return Json(serde_json::json!({
+            "success": false,
+            "message": "filter does not match the filter of discovery_params"
+        }));
+    }
+
     let file_name = if file_name_input.is_empty() {
         "order-management"
     } else {
@@ -835,6 +1471,9 @@ async fn process_cut_selected(
     let mut total_edges_added: Vec<(String, String, usize)> = payload.total_edges_added;
     let mut edge_modifications: Vec<EdgeModification> = payload.edge_modifications;
     let mut cost_to_add_edges: HashMap<(String, String), f64> = json_to_cost_to_add_edges(&payload.cost_to_add_edges);
+    let noise_removed_edges: Vec<(String, String, usize)> = payload.noise_removed_edges;
+    let filter: OCELFilter = discovery_options.filter.clone();
+    let interaction_pattern_support = payload.interaction_pattern_support;
 
     println!("old dfg:\n ");
     print_dfg(&dfg);
@@ -859,11 +1498,28 @@ async fn process_cut_selected(
     println!("new dfg:\n ");
     print_dfg(&dfg);
 
+    // Traces of the discovery the cuts were made on; the log is only prepared again when they
+    // were saved for other parameters
+    let traces = match load_traces(file_name, &discovery_options.params) {
+        Some(traces) => traces,
+        None => match prepare_divergence(file_name, &discovery_options) {
+            Ok((log_index, divergent_objects)) => add_self_loops::get_traces_from_index(&log_index, &divergent_objects),
+            Err(message) => {
+                return Json(serde_json::json!({
+                    "success": false,
+                    "message": message
+                }));
+            }
+        },
+    };
+
     let new_tree_node = create_new_tree_node_by_cut_selection(
         &dfg,
         &cut_selected,
         &global_start_activities,
         &global_end_activities,
+        &ocpt,
+        &traces,
     );
             
     
@@ -910,6 +1566,11 @@ async fn process_cut_selected(
         total_edges_removed: total_edges_removed.clone(),
         edge_modifications: edge_modifications,
         cost_to_add_edges: json_cost_to_add_edges,
+        noise_removed_edges,
+        filter,
+        interaction_pattern_support,
+        cut_strategies: cut_strategies.names(),
+        discovery_params: discovery_options.params.clone(),
         precision: 0.0,
         fitness: 0.0,
         f_score: 0.0,
@@ -934,7 +1595,8 @@ async fn process_cut_selected(
             &disjoint_activities,
             &global_start_activities,
             &global_end_activities,
-            &cost_to_add_edges
+            &cost_to_add_edges,
+            &cut_strategies,
         );
         response.cut_suggestions_list = cut_suggestions_list;
     } else {
@@ -967,7 +1629,16 @@ async fn process_cut_selected(
         // let (_, _, _, _, fitness, precision, f_score) = find_fitness_and_precision(&ocpt, file_name);
 
         // Get the modified OCPT with self-loops added
-        let (modified_ocpt, self_loop_activities) = add_self_loops(&dfg.clone(), &ocpt, file_name);
+        let (log_index, divergent_objects) = match prepare_divergence(file_name, &discovery_options) {
+            Ok(prepared) => prepared,
+            Err(message) => {
+                return Json(serde_json::json!({
+                    "success": false,
+                    "message": message
+                }));
+            }
+        };
+        let (modified_ocpt, self_loop_activities) = add_self_loops(&dfg.clone(), &ocpt, &log_index, &divergent_objects);
         println!("Self-loop activities processed: {:?}", self_loop_activities);
         
         // TEMP
@@ -1029,6 +1700,16 @@ async fn process_modify_node(
 ) -> Json<Value> {
     println!("Received modify-node request for node: {}", payload.selected_node_id);
 
+    let cut_strategies = match CutStrategies::from_names(&payload.cut_strategies) {
+        Ok(cut_strategies) => cut_strategies,
+        Err(message) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "message": message
+            }));
+        }
+    };
+
     let file_name = if file_name_input.is_empty() {
         "order-management"
     } else {
@@ -1044,6 +1725,9 @@ async fn process_modify_node(
     let mut total_edges_added: Vec<(String, String, usize)> = payload.total_edges_added;
     let mut edge_modifications: Vec<EdgeModification> = payload.edge_modifications;
     let mut cost_to_add_edges: HashMap<(String, String), f64> = json_to_cost_to_add_edges(&payload.cost_to_add_edges);
+    let noise_removed_edges: Vec<(String, String, usize)> = payload.noise_removed_edges;
+    let filter: OCELFilter = payload.filter;
+    let interaction_pattern_support = payload.interaction_pattern_support;
 
     // TODO: Implement node modification logic here
     
@@ -1097,6 +1781,11 @@ async fn process_modify_node(
         total_edges_removed: total_edges_removed.clone(),
         edge_modifications: edge_modifications,
         cost_to_add_edges: json_cost_to_add_edges,
+        noise_removed_edges,
+        filter,
+        interaction_pattern_support,
+        cut_strategies: cut_strategies.names(),
+        discovery_params: payload.discovery_params,
         precision: 0.0,
         fitness: 0.0,
         f_score: 0.0,
@@ -1112,7 +1801,8 @@ async fn process_modify_node(
         &removed_activities,
         &global_start_activities,
         &global_end_activities,
-        &cost_to_add_edges
+        &cost_to_add_edges,
+        &cut_strategies,
     );
     response.cut_suggestions_list = cut_suggestions_list;
 
@@ -1175,36 +1865,34 @@ fn create_new_tree_node_by_cut_selection(
     dfg: &HashMap<(String, String), usize>,
     cut_selected: &CutSuggestion,
     global_start_activities: &HashSet<String>,
-    global_end_activities: &HashSet<String>
+    global_end_activities: &HashSet<String>,
+    ocpt: &ProcessForest,
+    traces: &[ObjectTrace],
 ) -> TreeNode {
-    let process_forest_set1 = start_cuts_opti_v2::find_cuts_start(
-        dfg,
-        &cut_selected.set1,
-        global_start_activities,
-        global_end_activities,
-    );
-
-    let process_forest_set2 = start_cuts_opti_v2::find_cuts_start(
+    let sets: Vec<HashSet<String>> = cut_selected.sets().cloned().collect();
+    let process_forests = start_cuts_opti_v2::find_cuts_below_cut(
         dfg,
-        &cut_selected.set2,
+        &cut_selected.cut_type,
+        &sets,
         global_start_activities,
         global_end_activities,
+        traces,
     );
-
-    println!("Process Forest Set 1:\n ");
-    print_process_forest(&process_forest_set1);
-
-    println!("Process Forest Set 2:\n ");
-    print_process_forest(&process_forest_set2);
-
     let mut children = Vec::new();
-    children.extend(process_forest_set1);
-    children.extend(process_forest_set2);
-    TreeNode {
-        id: Uuid::new_v4().to_string(),
+    for (i, process_forest) in process_forests.into_iter().enumerate() {
+        println!("Process Forest Set {}:\n ", i + 1);
+        print_process_forest(&process_forest);
+
+        children.extend(process_forest);
+    }
+    let mut node = TreeNode {
+        id: String::new(),
         label: cut_selected.cut_type.clone(),
         children,
-    }
+    };
+    // The node replaces a flower of `ocpt`; its ids must not clash with the ones kept there
+    node_ids::assign_new_node_ids(std::slice::from_mut(&mut node), ocpt);
+    node
 }
 
 #[tokio::main]
@@ -1227,6 +1915,13 @@ async fn main() {
         .route("/modify-node", axum::routing::post(modify_node_handler_default))
         .route("/modify-node/:file_name", axum::routing::post(modify_node_handler))
         .route("/upload", axum::routing::post(upload_handler))
+        .route("/import-flat/:file_name", axum::routing::post(import_flat_handler))
+        .route("/validate/:file_name", get(validate_handler))
+        .route("/interaction-patterns/:file_name", get(interaction_patterns_handler))
+        .route("/object-type-dfgs/:file_name", get(object_type_dfgs_handler))
+        .route("/variants/:file_name", get(variants_handler))
+        .route("/statistics/:file_name", get(statistics_handler))
+        .route("/cut-finders", get(cut_finders_handler))
         .layer(DefaultBodyLimit::max(200 * 1024 * 1024)) // 200MB limit
         .layer(cors);
     
@@ -1238,6 +1933,13 @@ async fn main() {
     println!("  POST /cut-selected");
     println!("  POST /modify-node");
     println!("  POST /upload");
+    println!("  POST /import-flat/:file_name");
+    println!("  GET /validate/:file_name");
+    println!("  GET /interaction-patterns/:file_name");
+    println!("  GET /object-type-dfgs/:file_name");
+    println!("  GET /variants/:file_name");
+    println!("  GET /statistics/:file_name");
+    println!("  GET /cut-finders");
     println!("Server running on http://localhost:1080");
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:1080").await.unwrap();
@@ -1297,12 +1999,15 @@ fn filter_dfg(
         .collect()
 }
 
+// Both noise filters return the remaining DFG and the removed edges (sorted) with their weights
+type NoiseFiltered = (HashMap<(String, String), usize>, Vec<(String, String, usize)>);
+
 fn noise_reduction(
     mut dfg: HashMap<(String, String), usize>,
     n_threshold: f64,
-) -> HashMap<(String, String), usize> {
+) -> NoiseFiltered {
     if n_threshold <= 0.0 {
-        return dfg;
+        return (dfg, Vec::new());
     }
 
     let total_value: usize = dfg.values().sum();
@@ -1310,9 +2015,48 @@ fn noise_reduction(
 
     println!("Noise reduction: Total edges weight = {}, Threshold = {}, Cutoff = {}", total_value, n_threshold, cutoff);
 
-    dfg.retain(|_, &mut weight| weight > cutoff);
-    
-    dfg
+    let mut removed = Vec::new();
+    dfg.retain(|(from, to), &mut weight| {
+        if weight > cutoff {
+            return true;
+        }
+        removed.push((from.clone(), to.clone(), weight));
+        false
+    });
+    removed.sort();
+
+    (dfg, removed)
+}
+
+// IMf-style filter: an edge a -> b survives if its weight is at least `n_threshold` times
+// the weight of the strongest edge leaving a, so low-volume branches keep their main path
+fn noise_reduction_per_activity(
+    mut dfg: HashMap<(String, String), usize>,
+    n_threshold: f64,
+) -> NoiseFiltered {
+    if n_threshold <= 0.0 {
+        return (dfg, Vec::new());
+    }
+
+    let mut strongest_outgoing: HashMap<String, usize> = HashMap::new();
+    for ((from, _), &weight) in &dfg {
+        let max = strongest_outgoing.entry(from.clone()).or_insert(0);
+        *max = (*max).max(weight);
+    }
+
+    println!("Noise reduction per activity: Threshold = {}, Strongest outgoing edges = {:?}", n_threshold, strongest_outgoing);
+
+    let mut removed = Vec::new();
+    dfg.retain(|(from, to), &mut weight| {
+        if weight as f64 >= strongest_outgoing[from] as f64 * n_threshold {
+            return true;
+        }
+        removed.push((from.clone(), to.clone(), weight));
+        false
+    });
+    removed.sort();
+
+    (dfg, removed)
 }
 
 fn filter_activities(all_activities: &Vec<String>, remove_list: &Vec<String>) -> HashSet<String> {
@@ -2,7 +2,8 @@
 
 use std::collections::HashSet;
 use crate::types::{ProcessForest, TreeNode};
-use uuid::Uuid;
+use itertools::Itertools;
+use crate::node_ids::assign_missing_node_ids;
 
 // Given an Id of a node, this fn return a list of Ids of that node and all its descendants
 pub fn find_ids_of_descendants(forest: &ProcessForest, target_id: &str) -> Vec<String> {
@@ -40,7 +41,8 @@ fn collect_ids(node: &TreeNode, ids: &mut Vec<String>) {
 // Given an ID of a node and ocpt, remove that node and all its descendants from the ocpt. return the new ocpt as well as a list of all labels/activity names of the removed nodes
 pub fn replace_node_and_descendants(forest: ProcessForest, target_id: &str) -> (ProcessForest, HashSet<String>) {
     let mut removed_labels = HashSet::new();
-    let new_forest = replace_with_flower_recursive(forest, target_id, &mut removed_labels);
+    let mut new_forest = replace_with_flower_recursive(forest, target_id, &mut removed_labels);
+    assign_missing_node_ids(&mut new_forest);
 
     // Filter out structural nodes from removed_labels
     removed_labels.retain(|label| {
@@ -58,10 +60,10 @@ fn replace_with_flower_recursive(nodes: Vec<TreeNode>, target_id: &str, removed_
             collect_labels(&node, removed_labels);
             
             let mut children = Vec::new();
-            for label in removed_labels.iter() {
+            for label in removed_labels.iter().sorted() {
                 if !matches!(label.as_str(), "sequence" | "parallel" | "exclusive" | "redo" | "tau" | "flower") {
                     children.push(TreeNode {
-                        id: Uuid::new_v4().to_string(),
+                        id: String::new(),
                         label: label.clone(),
                         children: Vec::new(),
                     });
@@ -69,7 +71,7 @@ fn replace_with_flower_recursive(nodes: Vec<TreeNode>, target_id: &str, removed_
             }
 
             let flower_node = TreeNode {
-                id: Uuid::new_v4().to_string(),
+                id: String::new(),
                 label: "flower".to_string(),
                 children,
             };
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+
+use uuid::Builder;
+
+use crate::types::TreeNode;
+
+/// Gives every node of a forest an id derived from its subtree, replacing whatever ids the
+/// nodes had.
+///
+/// Discovering the same tree twice yields the same ids, so responses can be diffed across
+/// runs. Equal subtrees in several places (two `tau` leaves, say) are told apart by how many
+/// equal subtrees precede them in pre-order. Ids stay UUID-formatted, as conformance
+/// checking parses them.
+pub fn assign_node_ids(forest: &mut [TreeNode]) {
+    let mut ids = Ids::new(HashSet::new());
+    for tree in forest.iter_mut() {
+        ids.assign(tree, false);
+    }
+}
+
+/// Gives `nodes`, about to be placed into `existing`, ids derived from their subtrees that
+/// no node of `existing` has. The ids of `existing` are left alone, as edge modifications
+/// and clients refer to them.
+pub fn assign_new_node_ids(nodes: &mut [TreeNode], existing: &[TreeNode]) {
+    let mut taken = HashSet::new();
+    collect_ids(existing, &mut taken);
+    let mut ids = Ids::new(taken);
+    for node in nodes.iter_mut() {
+        ids.assign(node, false);
+    }
+}
+
+/// Gives the nodes of a forest with an empty id (the ones a modification just added) ids
+/// derived from their subtrees, keeping all other ids.
+pub fn assign_missing_node_ids(forest: &mut [TreeNode]) {
+    let mut taken = HashSet::new();
+    collect_ids(forest, &mut taken);
+    taken.remove("");
+    let mut ids = Ids::new(taken);
+    for tree in forest.iter_mut() {
+        ids.assign(tree, true);
+    }
+}
+
+fn collect_ids(forest: &[TreeNode], ids: &mut HashSet<String>) {
+    for node in forest {
+        ids.insert(node.id.clone());
+        collect_ids(&node.children, ids);
+    }
+}
+
+// Ids handed out so far, and how often every subtree signature has been numbered
+struct Ids {
+    taken: HashSet<String>,
+    occurrences: HashMap<String, usize>,
+}
+
+impl Ids {
+    fn new(taken: HashSet<String>) -> Self {
+        Ids { taken, occurrences: HashMap::new() }
+    }
+
+    fn assign(&mut self, node: &mut TreeNode, only_missing: bool) {
+        if !only_missing || node.id.is_empty() {
+            let signature = signature(node);
+            let occurrence = self.occurrences.entry(signature.clone()).or_insert(0);
+            // Skip the numbers whose id is in use already
+            node.id = loop {
+                let id = content_id(&format!("{}#{}", signature, occurrence));
+                *occurrence += 1;
+                if self.taken.insert(id.clone()) {
+                    break id;
+                }
+            };
+        }
+        for child in &mut node.children {
+            self.assign(child, only_missing);
+        }
+    }
+}
+
+// Label and children of a subtree, labels quoted so that activity names cannot clash with
+// the brackets
+fn signature(node: &TreeNode) -> String {
+    let children: Vec<String> = node.children.iter().map(signature).collect();
+    format!("{:?}({})", node.label, children.join(","))
+}
+
+// UUID built from two 64-bit FNV-1a hashes of `content`, mixed so that similar contents do
+// not give similar looking ids
+fn content_id(content: &str) -> String {
+    let high = mix(fnv1a(content.as_bytes(), 0xcbf2_9ce4_8422_2325));
+    let low = mix(fnv1a(content.as_bytes(), high));
+    let mut bytes = [0u8; 16];
+    bytes[..8].copy_from_slice(&high.to_be_bytes());
+    bytes[8..].copy_from_slice(&low.to_be_bytes());
+    Builder::from_custom_bytes(bytes).into_uuid().to_string()
+}
+
+fn fnv1a(bytes: &[u8], offset_basis: u64) -> u64 {
+    bytes.iter().fold(offset_basis, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+// SplitMix64 finalizer
+fn mix(mut hash: u64) -> u64 {
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::start_cuts_opti_v2::find_cuts_start;
+
+    fn leaf(label: &str) -> TreeNode {
+        TreeNode { id: String::new(), label: label.to_string(), children: Vec::new() }
+    }
+
+    fn node(label: &str, children: Vec<TreeNode>) -> TreeNode {
+        TreeNode { id: String::new(), label: label.to_string(), children }
+    }
+
+    fn ids(forest: &[TreeNode]) -> Vec<String> {
+        forest.iter().flat_map(|node| std::iter::once(node.id.clone()).chain(ids(&node.children))).collect()
+    }
+
+    #[test]
+    fn equal_subtrees_get_distinct_ids() {
+        let mut forest = vec![node("sequence", vec![
+            node("exclusive", vec![leaf("a"), leaf("tau")]),
+            node("exclusive", vec![leaf("b"), leaf("tau")]),
+        ])];
+        assign_node_ids(&mut forest);
+        let all = ids(&forest);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len());
+
+        let mut again = forest.clone();
+        assign_node_ids(&mut again);
+        assert_eq!(ids(&again), all);
+    }
+
+    #[test]
+    fn new_nodes_keep_clear_of_existing_ids() {
+        let mut forest = vec![node("sequence", vec![leaf("a"), node("flower", vec![leaf("b"), leaf("c")])])];
+        assign_node_ids(&mut forest);
+        let existing = ids(&forest);
+
+        let mut replacement = node("flower", vec![leaf("b"), leaf("c")]);
+        assign_new_node_ids(std::slice::from_mut(&mut replacement), &forest);
+        assert!(ids(&[replacement]).iter().all(|id| !existing.contains(id)));
+        assert_eq!(ids(&forest), existing);
+    }
+
+    #[test]
+    fn discovery_is_the_same_every_run() {
+        let edges = [("a", "b", 3), ("a", "c", 2), ("b", "d", 3), ("c", "d", 2), ("d", "e", 4), ("e", "d", 1)];
+        let discover = || {
+            // Fresh maps and sets iterate in a different order every time
+            let dfg: HashMap<(String, String), usize> =
+                edges.iter().map(|(from, to, weight)| ((from.to_string(), to.to_string()), *weight)).collect();
+            let activities: HashSet<String> = ["a", "b", "c", "d", "e"].iter().map(|a| a.to_string()).collect();
+            let start: HashSet<String> = HashSet::from(["a".to_string()]);
+            let end: HashSet<String> = HashSet::from(["e".to_string()]);
+            serde_json::to_string(&find_cuts_start(&dfg, &activities, &start, &end)).unwrap()
+        };
+        let first = discover();
+        for _ in 0..10 {
+            assert_eq!(discover(), first);
+        }
+    }
+}
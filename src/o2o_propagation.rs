@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{OCELRelationship, OCEL};
+
+/// Which object types inherit the events of the objects they are O2O-linked to.
+///
+/// A rule `(item, package)` gives every `item` the events of each `package` it has an O2O
+/// relationship with, as if the log had E2O relationships for them. O2O links are followed in
+/// both directions but only one hop far, and `*` matches any object type.
+///
+/// With `o2o=item:package`, `item` becomes related to the activities of its packages, and
+/// convergent at those where a package holds several items. Which links to follow is left to
+/// the user, as O2O links also record associations (an employee and the orders they handled)
+/// that should not merge object lifecycles; `o2o_patterns` follows every link instead, but
+/// only for types an activity has no E2O relationship to, see
+/// [`crate::interaction_patterns::relate_o2o_linked_objects`].
+#[derive(Debug, Clone, Default)]
+pub struct O2OPropagation {
+    pub rules: Vec<(String, String)>,
+}
+
+impl O2OPropagation {
+    /// Parses the `o2o` query parameter: comma separated `inheriting_type:source_type` pairs.
+    pub fn from_param(o2o: Option<&str>) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for rule in o2o.unwrap_or("").split(',').map(str::trim).filter(|rule| !rule.is_empty()) {
+            match rule.split_once(':') {
+                Some((inheriting, source)) if !inheriting.trim().is_empty() && !source.trim().is_empty() => {
+                    rules.push((inheriting.trim().to_string(), source.trim().to_string()))
+                }
+                _ => return Err(format!("Invalid o2o rule '{}', expected inheriting_type:source_type", rule)),
+            }
+        }
+        Ok(O2OPropagation { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    fn applies(&self, inheriting_type: &str, source_type: &str) -> bool {
+        self.rules.iter().any(|(inheriting, source)| {
+            (inheriting == "*" || inheriting == inheriting_type) && (source == "*" || source == source_type)
+        })
+    }
+
+    /// Adds an E2O relationship to every event of a source object for each object inheriting
+    /// from it; the new relationships carry the O2O qualifier. Returns the log and the number
+    /// of relationships added.
+    pub fn apply(&self, mut ocel: OCEL) -> (OCEL, usize) {
+        if self.is_empty() {
+            return (ocel, 0);
+        }
+
+        let object_types: HashMap<&str, &str> = ocel
+            .objects
+            .iter()
+            .map(|object| (object.id.as_str(), object.object_type.as_str()))
+            .collect();
+
+        // source object -> objects inheriting its events, with the O2O qualifier
+        let mut inheritors: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        let mut link = |inheriting: &str, source: &str, qualifier: &str| {
+            let (Some(inheriting_type), Some(source_type)) = (object_types.get(inheriting), object_types.get(source)) else {
+                return;
+            };
+            if inheriting != source && self.applies(inheriting_type, source_type) {
+                let targets = inheritors.entry(source.to_string()).or_default();
+                if !targets.iter().any(|(id, _)| id == inheriting) {
+                    targets.push((inheriting.to_string(), qualifier.to_string()));
+                }
+            }
+        };
+        for object in &ocel.objects {
+            for relationship in &object.relationships {
+                link(&object.id, &relationship.object_id, &relationship.qualifier);
+                link(&relationship.object_id, &object.id, &relationship.qualifier);
+            }
+        }
+
+        let mut added = 0;
+        for event in &mut ocel.events {
+            let mut related: HashSet<String> = event.relationships.iter().map(|r| r.object_id.clone()).collect();
+            let mut inherited = Vec::new();
+            for relationship in &event.relationships {
+                for (inheriting, qualifier) in inheritors.get(&relationship.object_id).into_iter().flatten() {
+                    if related.insert(inheriting.clone()) {
+                        inherited.push(OCELRelationship { object_id: inheriting.clone(), qualifier: qualifier.clone() });
+                    }
+                }
+            }
+            added += inherited.len();
+            event.relationships.extend(inherited);
+        }
+
+        (ocel, added)
+    }
+}
@@ -0,0 +1,339 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{parse_timestamp, OCELAttributeValue, OCELEvent, OCELObject, OCEL};
+
+/// Filters applied to a log before discovery, passed as JSON in the `filter` query parameter.
+///
+/// All parts are optional and combined with AND:
+///
+/// ```json
+/// {
+///   "from": "2023-01-01T00:00:00Z",
+///   "to": "2023-04-01T00:00:00Z",
+///   "event_attributes": [{ "name": "resource", "op": "eq", "value": "Alice" }],
+///   "object_attributes": [{ "name": "price", "type": "orders", "op": "gt", "value": 100 }],
+///   "lifecycle": [{ "object_type": "orders", "activity": "pay order", "condition": "reached" }]
+/// }
+/// ```
+///
+/// Objects that are filtered out take their E2O and O2O relationships with them, and events
+/// left without any object are dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct OCELFilter {
+    /// Keeps events at or after this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<FixedOffset>>,
+    /// Keeps events before this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub event_attributes: Vec<AttributePredicate>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub object_attributes: Vec<AttributePredicate>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lifecycle: Vec<LifecyclePredicate>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// Substring match on string values
+    Contains,
+}
+
+/// Condition on a named attribute. Events or objects without the attribute do not match.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AttributePredicate {
+    pub name: String,
+    /// Only applies to events (objects) of this type; others are kept as they are
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    pub op: Comparison,
+    pub value: OCELAttributeValue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleCondition {
+    /// The object has at least one event of the activity
+    #[default]
+    Reached,
+    /// The object has no event of the activity
+    NotReached,
+    /// The first event of the object is of the activity
+    StartsWith,
+    /// The last event of the object is of the activity
+    EndsWith,
+}
+
+/// Keeps only the objects of `object_type` whose lifecycle satisfies the condition;
+/// objects of other types are not affected.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LifecyclePredicate {
+    pub object_type: String,
+    pub activity: String,
+    #[serde(default)]
+    pub condition: LifecycleCondition,
+}
+
+impl OCELFilter {
+    /// Parses the JSON `filter` query parameter; no parameter means no filter.
+    pub fn from_param(filter: Option<&str>) -> Result<Self, String> {
+        match filter {
+            None => Ok(OCELFilter::default()),
+            Some(filter) if filter.trim().is_empty() => Ok(OCELFilter::default()),
+            Some(filter) => serde_json::from_str(filter).map_err(|e| format!("Invalid filter: {}", e)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == OCELFilter::default()
+    }
+
+    /// Names of the event and object attributes the predicates read.
+    pub fn attribute_names(&self) -> HashSet<String> {
+        self.event_attributes
+            .iter()
+            .chain(&self.object_attributes)
+            .map(|predicate| predicate.name.clone())
+            .collect()
+    }
+
+    /// Applies the filter; the lifecycle conditions see the events that passed the time
+    /// window and the event attribute predicates.
+    pub fn apply(&self, ocel: OCEL) -> OCEL {
+        if self.is_empty() {
+            return ocel;
+        }
+
+        let mut events: Vec<OCELEvent> = ocel
+            .events
+            .into_iter()
+            .filter(|event| self.from.is_none_or(|from| event.time >= from))
+            .filter(|event| self.to.is_none_or(|to| event.time < to))
+            .filter(|event| {
+                self.event_attributes
+                    .iter()
+                    .all(|predicate| predicate.matches_event(event))
+            })
+            .collect();
+
+        let mut kept_objects: HashSet<String> = ocel
+            .objects
+            .iter()
+            .filter(|object| {
+                self.object_attributes
+                    .iter()
+                    .all(|predicate| predicate.matches_object(object))
+            })
+            .map(|object| object.id.clone())
+            .collect();
+
+        if !self.lifecycle.is_empty() {
+            let object_types: HashMap<&str, &str> = ocel
+                .objects
+                .iter()
+                .map(|object| (object.id.as_str(), object.object_type.as_str()))
+                .collect();
+            let failing = self.lifecycle_failures(&events, &object_types);
+            kept_objects.retain(|id| !failing.contains(id.as_str()));
+        }
+
+        for event in &mut events {
+            event.relationships.retain(|r| kept_objects.contains(&r.object_id));
+        }
+        events.retain(|event| !event.relationships.is_empty());
+
+        let objects = ocel
+            .objects
+            .into_iter()
+            .filter(|object| kept_objects.contains(&object.id))
+            .map(|mut object| {
+                object.relationships.retain(|r| kept_objects.contains(&r.object_id));
+                object
+            })
+            .collect();
+
+        OCEL {
+            event_types: ocel.event_types,
+            object_types: ocel.object_types,
+            events,
+            objects,
+        }
+    }
+
+    // Ids of the objects that fail one of the lifecycle conditions
+    fn lifecycle_failures<'a>(&self, events: &[OCELEvent], object_types: &HashMap<&'a str, &'a str>) -> HashSet<&'a str> {
+        // Activities of every object in time order
+        let mut lifecycles: HashMap<&'a str, Vec<(DateTime<FixedOffset>, &str)>> = HashMap::new();
+        for event in events {
+            for relationship in &event.relationships {
+                if let Some((&id, _)) = object_types.get_key_value(relationship.object_id.as_str()) {
+                    lifecycles.entry(id).or_default().push((event.time, event.event_type.as_str()));
+                }
+            }
+        }
+        for lifecycle in lifecycles.values_mut() {
+            lifecycle.sort_by_key(|(time, _)| *time);
+        }
+
+        let mut failing = HashSet::new();
+        for (&id, &object_type) in object_types {
+            let lifecycle = lifecycles.get(id).map(Vec::as_slice).unwrap_or(&[]);
+            let passes = self
+                .lifecycle
+                .iter()
+                .filter(|predicate| predicate.object_type == object_type)
+                .all(|predicate| predicate.matches(lifecycle));
+            if !passes {
+                failing.insert(id);
+            }
+        }
+        failing
+    }
+}
+
+impl LifecyclePredicate {
+    fn matches(&self, lifecycle: &[(DateTime<FixedOffset>, &str)]) -> bool {
+        match self.condition {
+            LifecycleCondition::Reached => lifecycle.iter().any(|(_, activity)| *activity == self.activity),
+            LifecycleCondition::NotReached => lifecycle.iter().all(|(_, activity)| *activity != self.activity),
+            LifecycleCondition::StartsWith => lifecycle.first().is_some_and(|(_, activity)| *activity == self.activity),
+            LifecycleCondition::EndsWith => lifecycle.last().is_some_and(|(_, activity)| *activity == self.activity),
+        }
+    }
+}
+
+impl AttributePredicate {
+    fn matches_event(&self, event: &OCELEvent) -> bool {
+        if self.type_name.as_ref().is_some_and(|t| *t != event.event_type) {
+            return true;
+        }
+        event
+            .attributes
+            .iter()
+            .find(|attribute| attribute.name == self.name)
+            .is_some_and(|attribute| self.matches_value(&attribute.value))
+    }
+
+    // Object attributes change over time; the latest value is the one compared
+    fn matches_object(&self, object: &OCELObject) -> bool {
+        if self.type_name.as_ref().is_some_and(|t| *t != object.object_type) {
+            return true;
+        }
+        object
+            .attributes
+            .iter()
+            .filter(|attribute| attribute.name == self.name)
+            .max_by_key(|attribute| attribute.time)
+            .is_some_and(|attribute| self.matches_value(&attribute.value))
+    }
+
+    fn matches_value(&self, value: &OCELAttributeValue) -> bool {
+        if self.op == Comparison::Contains {
+            return match (value, &self.value) {
+                (OCELAttributeValue::String(value), OCELAttributeValue::String(needle)) => value.contains(needle.as_str()),
+                _ => false,
+            };
+        }
+
+        let Some(ordering) = compare_values(value, &self.value) else {
+            return false;
+        };
+        match self.op {
+            Comparison::Eq => ordering == Ordering::Equal,
+            Comparison::Ne => ordering != Ordering::Equal,
+            Comparison::Gt => ordering == Ordering::Greater,
+            Comparison::Ge => ordering != Ordering::Less,
+            Comparison::Lt => ordering == Ordering::Less,
+            Comparison::Le => ordering != Ordering::Greater,
+            Comparison::Contains => unreachable!(),
+        }
+    }
+}
+
+fn as_number(value: &OCELAttributeValue) -> Option<f64> {
+    match value {
+        OCELAttributeValue::Integer(i) => Some(*i as f64),
+        OCELAttributeValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn as_time(value: &OCELAttributeValue) -> Option<DateTime<FixedOffset>> {
+    match value {
+        OCELAttributeValue::Time(time) => Some(*time),
+        OCELAttributeValue::String(s) => parse_timestamp(s),
+        _ => None,
+    }
+}
+
+// Orders two attribute values of compatible kinds: numbers with numbers, times with times
+// (or strings that parse as times), strings and booleans with their own kind
+fn compare_values(a: &OCELAttributeValue, b: &OCELAttributeValue) -> Option<Ordering> {
+    match (a, b) {
+        (OCELAttributeValue::String(a), OCELAttributeValue::String(b)) => Some(a.cmp(b)),
+        (OCELAttributeValue::Boolean(a), OCELAttributeValue::Boolean(b)) => Some(a.cmp(b)),
+        _ => {
+            if let (Some(a), Some(b)) = (as_number(a), as_number(b)) {
+                return a.partial_cmp(&b);
+            }
+            Some(as_time(a)?.cmp(&as_time(b)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OCELObjectAttribute, OCELRelationship};
+
+    #[test]
+    fn untyped_predicate_drops_objects_without_the_attribute() {
+        let time: DateTime<FixedOffset> = "2024-01-01T00:00:00Z".parse().unwrap();
+        let object = |id: &str, object_type: &str, price: Option<i64>| OCELObject {
+            id: id.to_string(),
+            object_type: object_type.to_string(),
+            attributes: price
+                .map(|price| OCELObjectAttribute { name: "price".to_string(), value: OCELAttributeValue::Integer(price), time })
+                .into_iter()
+                .collect(),
+            relationships: Vec::new(),
+        };
+        let ocel = OCEL {
+            event_types: Vec::new(),
+            object_types: Vec::new(),
+            events: vec![OCELEvent {
+                id: "e1".to_string(),
+                event_type: "place order".to_string(),
+                time,
+                attributes: Vec::new(),
+                relationships: ["o1", "o2", "c1"]
+                    .iter()
+                    .map(|id| OCELRelationship { object_id: id.to_string(), qualifier: String::new() })
+                    .collect(),
+            }],
+            objects: vec![object("o1", "order", Some(150)), object("o2", "order", Some(50)), object("c1", "customer", None)],
+        };
+
+        let filter = OCELFilter::from_param(Some(r#"{"object_attributes": [{"name": "price", "op": "gt", "value": 100}]}"#)).unwrap();
+        let ocel = filter.apply(ocel);
+
+        let objects: Vec<&str> = ocel.objects.iter().map(|object| object.id.as_str()).collect();
+        assert_eq!(objects, ["o1"]);
+        assert_eq!(ocel.events[0].relationships.len(), 1);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+use std::fs as stdfs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use process_mining::core::event_data::object_centric::ocel_sql::import_ocel_sqlite_from_path;
+use process_mining::core::event_data::object_centric::ocel_xml::import_ocel_xml_path;
+
+use crate::legacy_ocel_import;
+use crate::ocel_stream::{self, AttributeSelection, CompactOCEL, OCELSink};
+use crate::types::OCEL;
+
+/// File extensions accepted for OCEL logs, in the order they are tried when
+/// resolving `data/{file_name}` without an extension.
+pub const SUPPORTED_EXTENSIONS: [&str; 6] = ["json", "jsonocel", "xmlocel", "xml", "sqlite", "db"];
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+
+/// On-disk formats of an OCEL log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OCELFormat {
+    Json,
+    Xml,
+    Sqlite,
+    /// OCEL 1.0 JSON (`ocel:events`, `ocel:objects`)
+    LegacyJson,
+    /// OCEL 1.0 XML (`<global>` sections, `omap`/`vmap` lists)
+    LegacyXml,
+}
+
+impl OCELFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OCELFormat::Json => "json",
+            OCELFormat::Xml => "xml",
+            OCELFormat::Sqlite => "sqlite",
+            OCELFormat::LegacyJson => "ocel1-json",
+            OCELFormat::LegacyXml => "ocel1-xml",
+        }
+    }
+}
+
+/// Guesses the format from the file extension only.
+pub fn format_from_extension(path: &Path) -> Option<OCELFormat> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "json" | "jsonocel" => Some(OCELFormat::Json),
+        "xml" | "xmlocel" => Some(OCELFormat::Xml),
+        "sqlite" | "db" => Some(OCELFormat::Sqlite),
+        _ => None,
+    }
+}
+
+/// Detects the format from the first bytes of the file.
+///
+/// SQLite databases start with a fixed header, JSON logs with `{` and XML logs with `<`
+/// (ignoring a UTF-8 BOM and leading whitespace). OCEL 1.0 logs are told apart by their
+/// `ocel:` prefixed JSON keys or the `<global>` XML sections at the top of the file.
+pub fn sniff_format(path: &Path) -> Option<OCELFormat> {
+    let mut file = stdfs::File::open(path).ok()?;
+    let mut header = [0u8; 4096];
+    let read = file.read(&mut header).ok()?;
+    let header = &header[..read];
+
+    if header.starts_with(SQLITE_MAGIC) {
+        return Some(OCELFormat::Sqlite);
+    }
+
+    let header = header.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(header);
+    let contains = |needle: &[u8]| header.windows(needle.len()).any(|w| w == needle);
+    match header.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'{') if contains(b"\"ocel:") => Some(OCELFormat::LegacyJson),
+        Some(b'{') => Some(OCELFormat::Json),
+        Some(b'<') if contains(b"<global") => Some(OCELFormat::LegacyXml),
+        Some(b'<') => Some(OCELFormat::Xml),
+        _ => None,
+    }
+}
+
+/// Detects the format of a log, preferring the file content over the extension.
+pub fn detect_format(path: &Path) -> Option<OCELFormat> {
+    sniff_format(path).or_else(|| format_from_extension(path))
+}
+
+/// Finds the log file for `file_name` inside `data/`.
+///
+/// `file_name` may carry its extension (`order-management.xmlocel`) or not, in which case
+/// every supported extension is tried in turn.
+pub fn resolve_ocel_path(file_name: &str) -> Option<PathBuf> {
+    let direct = Path::new("data").join(file_name);
+    if format_from_extension(&direct).is_some() && direct.is_file() {
+        return Some(direct);
+    }
+
+    SUPPORTED_EXTENSIONS
+        .iter()
+        .map(|extension| PathBuf::from(format!("data/{}.{}", file_name, extension)))
+        .find(|path| path.is_file())
+}
+
+/// Loads the log stored as `data/{file_name}.*` in any supported format.
+pub fn load_ocel(file_name: &str) -> Result<OCEL, String> {
+    let path = resolve_ocel_path(file_name)
+        .ok_or_else(|| format!("No OCEL log found for '{}' in data/", file_name))?;
+    import_ocel_path(&path)
+}
+
+/// Loads `data/{file_name}.*` into the compact, indexed representation.
+///
+/// JSON logs are streamed straight into it; other formats are imported first.
+pub fn load_compact_ocel(file_name: &str) -> Result<CompactOCEL, String> {
+    let mut compact = CompactOCEL::new();
+    load_into(file_name, &mut compact)?;
+    Ok(compact)
+}
+
+/// Loads `data/{file_name}.*` with only the event and object attributes named in `keep`.
+pub fn load_ocel_with_attributes(file_name: &str, keep: &HashSet<String>) -> Result<OCEL, String> {
+    let mut ocel = OCEL { event_types: Vec::new(), object_types: Vec::new(), events: Vec::new(), objects: Vec::new() };
+    load_into(file_name, &mut AttributeSelection { sink: &mut ocel, keep })?;
+    Ok(ocel)
+}
+
+// Streams JSON logs into `sink`; other formats are imported first and then handed over
+fn load_into<S: OCELSink>(file_name: &str, sink: &mut S) -> Result<(), String> {
+    let path = resolve_ocel_path(file_name)
+        .ok_or_else(|| format!("No OCEL log found for '{}' in data/", file_name))?;
+
+    if detect_format(&path) == Some(OCELFormat::Json) {
+        let file = stdfs::File::open(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        return ocel_stream::stream_ocel_json(file, sink)
+            .map_err(|e| format!("Failed to parse OCEL JSON {}: {}", path.display(), e));
+    }
+
+    ocel_stream::feed_ocel(import_ocel_path(&path)?, sink);
+    Ok(())
+}
+
+/// Imports a log from `path`, converting XML, SQLite and OCEL 1.0 logs into [`OCEL`].
+pub fn import_ocel_path(path: &Path) -> Result<OCEL, String> {
+    let format = detect_format(path)
+        .ok_or_else(|| format!("Unrecognized OCEL format: {}", path.display()))?;
+
+    println!("Importing {} as OCEL {}", path.display(), format.as_str());
+
+    match format {
+        OCELFormat::Json => ocel_stream::read_ocel_json(path),
+        OCELFormat::Xml => {
+            let ocel = import_ocel_xml_path(path)
+                .map_err(|e| format!("Failed to parse OCEL XML {}: {}", path.display(), e))?;
+            convert_process_mining_ocel(ocel)
+        }
+        OCELFormat::Sqlite => {
+            let ocel = import_ocel_sqlite_from_path(path)
+                .map_err(|e| format!("Failed to read OCEL SQLite {}: {}", path.display(), e))?;
+            convert_process_mining_ocel(ocel)
+        }
+        OCELFormat::LegacyJson => legacy_ocel_import::import_ocel1_json(path),
+        OCELFormat::LegacyXml => legacy_ocel_import::import_ocel1_xml(path),
+    }
+}
+
+// Both OCEL structs share the OCEL 2.0 JSON layout, so convert through serde
+// (same approach as conformance_format for the conformance types)
+fn convert_process_mining_ocel(ocel: process_mining::OCEL) -> Result<OCEL, String> {
+    let ocel_json = serde_json::to_value(&ocel).map_err(|e| format!("Failed to serialize OCEL: {}", e))?;
+    drop(ocel);
+    serde_json::from_value(ocel_json).map_err(|e| format!("Failed to convert OCEL: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes `content` to a file of its own in the temp directory and sniffs it
+    fn sniff(name: &str, content: &[u8]) -> Option<OCELFormat> {
+        let path = std::env::temp_dir().join(format!("sniff-{}-{}", std::process::id(), name));
+        stdfs::write(&path, content).unwrap();
+        let format = sniff_format(&path);
+        stdfs::remove_file(&path).unwrap();
+        format
+    }
+
+    #[test]
+    fn formats_are_sniffed_from_the_content() {
+        assert_eq!(sniff("db", b"SQLite format 3\0rest of the header"), Some(OCELFormat::Sqlite));
+        assert_eq!(sniff("json", b"\xEF\xBB\xBF \n{\"objectTypes\": []}"), Some(OCELFormat::Json));
+        assert_eq!(sniff("xml", b"<?xml version=\"1.0\"?><log><object-types/></log>"), Some(OCELFormat::Xml));
+        assert_eq!(sniff("csv", b"event,activity\n"), None);
+    }
+
+    #[test]
+    fn legacy_logs_are_told_apart_by_their_markers() {
+        assert_eq!(sniff("legacy-json", b"{\"ocel:global-log\": {}}"), Some(OCELFormat::LegacyJson));
+        assert_eq!(sniff("legacy-xml", b"<log><global scope=\"log\"/></log>"), Some(OCELFormat::LegacyXml));
+    }
+
+    #[test]
+    fn content_wins_over_the_extension() {
+        let path = std::env::temp_dir().join(format!("sniff-{}-legacy.json", std::process::id()));
+        stdfs::write(&path, b"{\"ocel:events\": {}}").unwrap();
+        let format = detect_format(&path);
+        stdfs::remove_file(&path).unwrap();
+        assert_eq!(format, Some(OCELFormat::LegacyJson));
+        assert_eq!(format_from_extension(Path::new("log.xmlocel")), Some(OCELFormat::Xml));
+    }
+}
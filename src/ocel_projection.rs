@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+
+use crate::ocel_stream::CompactOCEL;
+use crate::types::OCEL;
+
+/// Object types and activities discovery is restricted to.
+///
+/// An `include_*` list keeps only the named entries, an `exclude_*` list drops them; both
+/// can be combined. An empty projection keeps the whole log.
+#[derive(Debug, Clone, Default)]
+pub struct LogProjection {
+    pub include_object_types: Option<HashSet<String>>,
+    pub exclude_object_types: HashSet<String>,
+    pub include_activities: Option<HashSet<String>>,
+    pub exclude_activities: HashSet<String>,
+}
+
+// Comma separated list from a query parameter, ignoring blanks
+fn parse_list(list: Option<&str>) -> HashSet<String> {
+    list.unwrap_or("")
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn parse_include(list: Option<&str>) -> Option<HashSet<String>> {
+    list.map(|list| parse_list(Some(list))).filter(|names| !names.is_empty())
+}
+
+impl LogProjection {
+    /// Builds the projection from the comma separated `include_object_types`,
+    /// `exclude_object_types`, `include_activities` and `exclude_activities` query parameters.
+    pub fn from_params(
+        include_object_types: Option<&str>,
+        exclude_object_types: Option<&str>,
+        include_activities: Option<&str>,
+        exclude_activities: Option<&str>,
+    ) -> Self {
+        LogProjection {
+            include_object_types: parse_include(include_object_types),
+            exclude_object_types: parse_list(exclude_object_types),
+            include_activities: parse_include(include_activities),
+            exclude_activities: parse_list(exclude_activities),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include_object_types.is_none()
+            && self.exclude_object_types.is_empty()
+            && self.include_activities.is_none()
+            && self.exclude_activities.is_empty()
+    }
+
+    pub fn keeps_object_type(&self, object_type: &str) -> bool {
+        self.include_object_types.as_ref().is_none_or(|include| include.contains(object_type))
+            && !self.exclude_object_types.contains(object_type)
+    }
+
+    pub fn keeps_activity(&self, activity: &str) -> bool {
+        self.include_activities.as_ref().is_none_or(|include| include.contains(activity))
+            && !self.exclude_activities.contains(activity)
+    }
+
+    /// Activities of a log's events (`activities`, repeats allowed) that the projection
+    /// drops, sorted
+    pub fn removed_activities<'a>(&self, activities: impl Iterator<Item = &'a str>) -> Vec<String> {
+        let mut removed: Vec<String> = activities
+            .filter(|activity| !self.keeps_activity(activity))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        removed.sort();
+        removed
+    }
+
+    /// Projects the log onto the selected object types and activities.
+    ///
+    /// Objects of dropped types disappear together with every E2O and O2O relationship
+    /// pointing at them, events of dropped activities are removed, and events left without
+    /// any object are removed as well since they no longer belong to any trace.
+    pub fn apply(&self, ocel: OCEL) -> OCEL {
+        if self.is_empty() {
+            return ocel;
+        }
+
+        let objects: Vec<_> = ocel
+            .objects
+            .into_iter()
+            .filter(|object| self.keeps_object_type(&object.object_type))
+            .collect();
+        let kept_objects: HashSet<String> = objects.iter().map(|object| object.id.clone()).collect();
+
+        let objects = objects
+            .into_iter()
+            .map(|mut object| {
+                object.relationships.retain(|r| kept_objects.contains(&r.object_id));
+                object
+            })
+            .collect();
+
+        let events = ocel
+            .events
+            .into_iter()
+            .filter(|event| self.keeps_activity(&event.event_type))
+            .filter_map(|mut event| {
+                event.relationships.retain(|r| kept_objects.contains(&r.object_id));
+                (!event.relationships.is_empty()).then_some(event)
+            })
+            .collect();
+
+        OCEL {
+            event_types: ocel.event_types.into_iter().filter(|t| self.keeps_activity(&t.name)).collect(),
+            object_types: ocel.object_types.into_iter().filter(|t| self.keeps_object_type(&t.name)).collect(),
+            events,
+            objects,
+        }
+    }
+
+    /// [`LogProjection::apply`] on the compact representation
+    pub fn apply_compact(&self, mut compact: CompactOCEL) -> CompactOCEL {
+        if !self.is_empty() {
+            compact.retain(|activity| self.keeps_activity(activity), |object_type| self.keeps_object_type(object_type));
+        }
+        compact
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocel_stream;
+    use crate::types::{OCELEvent, OCELObject, OCELRelationship};
+
+    #[test]
+    fn removed_activities_are_the_ones_not_kept() {
+        let projection = LogProjection::from_params(None, None, Some("place order, pack, ship"), Some("pack"));
+        let activities = ["place order", "pack", "pick item", "ship", "pack"];
+
+        assert_eq!(projection.removed_activities(activities.into_iter()), ["pack", "pick item"]);
+        assert!(LogProjection::default().removed_activities(activities.into_iter()).is_empty());
+    }
+
+    #[test]
+    fn events_left_without_objects_are_dropped() {
+        let object = |id: &str, object_type: &str| OCELObject {
+            id: id.to_string(),
+            object_type: object_type.to_string(),
+            attributes: Vec::new(),
+            relationships: Vec::new(),
+        };
+        let event = |id: &str, activity: &str, objects: &[&str]| OCELEvent {
+            id: id.to_string(),
+            event_type: activity.to_string(),
+            time: "2024-01-01T00:00:00Z".parse().unwrap(),
+            attributes: Vec::new(),
+            relationships: objects
+                .iter()
+                .map(|object| OCELRelationship { object_id: object.to_string(), qualifier: String::new() })
+                .collect(),
+        };
+        let ocel = OCEL {
+            event_types: Vec::new(),
+            object_types: Vec::new(),
+            events: vec![event("e1", "place order", &["o1", "i1"]), event("e2", "pick item", &["i1"])],
+            objects: vec![object("o1", "order"), object("i1", "item")],
+        };
+        let mut compact = CompactOCEL::new();
+        ocel_stream::feed_ocel(ocel, &mut compact);
+
+        let projection = LogProjection::from_params(None, Some("item"), None, None);
+        let compact = projection.apply_compact(compact);
+
+        let events: Vec<(&str, usize)> = compact.events.iter().map(|event| (&*event.id, event.relationships.len())).collect();
+        assert_eq!(events, [("e1", 1)]);
+        assert_eq!(compact.objects.len(), 1);
+        assert_eq!(&*compact.objects[0].id, "o1");
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::divergence_free_dfg::waiting_seconds;
+use crate::event_log_index::{EventId, EventLogIndex, ObjectId, ObjectTypeId, TieBreak};
+use crate::ocel_stream::{CompactOCEL, UNDECLARED};
+use crate::types::Distribution;
+
+/// Descriptive statistics of a log, computed before any modelling
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LogStatistics {
+    pub events: usize,
+    pub objects: usize,
+    /// Per object type, sorted by name
+    pub object_types: Vec<ObjectTypeStatistics>,
+}
+
+/// Lifecycle and throughput statistics of the objects of one type
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ObjectTypeStatistics {
+    pub object_type: String,
+    pub objects: usize,
+    /// Objects that take part in at least one event
+    pub objects_with_events: usize,
+    /// Number of events per object, objects without events included
+    pub events_per_object: Option<Distribution>,
+    /// Seconds from the first to the last event of every object with events
+    pub lifecycle_duration: Option<Distribution>,
+    /// Number of objects whose lifecycle starts with each activity
+    pub first_activities: BTreeMap<String, usize>,
+    /// Number of objects whose lifecycle ends with each activity
+    pub last_activities: BTreeMap<String, usize>,
+    /// Number of events of each activity that involve objects of the type
+    pub activities: BTreeMap<String, usize>,
+    /// Per other object type, how many of its objects each object with events shares an
+    /// event with
+    pub co_occurring_objects: BTreeMap<String, Distribution>,
+}
+
+// Per-object values of one object type, gathered before they are summarised
+#[derive(Default)]
+struct Collected {
+    events_per_object: Vec<f64>,
+    lifecycle_duration: Vec<f64>,
+    first_activities: BTreeMap<String, usize>,
+    last_activities: BTreeMap<String, usize>,
+    // Events already counted in `activities`
+    activity_events: HashSet<EventId>,
+    activities: BTreeMap<String, usize>,
+    co_occurring: HashMap<ObjectTypeId, Vec<f64>>,
+}
+
+/// Computes the statistics of every object type over the relations of `compact`; `tie_break`
+/// orders events with equal timestamps when picking first and last activities.
+pub fn get_statistics(compact: CompactOCEL, tie_break: &TieBreak) -> LogStatistics {
+    // Declared objects per type; the compact model already holds every object id once
+    let mut declared: BTreeMap<String, usize> = BTreeMap::new();
+    for object in compact.objects.iter().filter(|object| object.object_type != UNDECLARED) {
+        *declared.entry(compact.object_types[object.object_type as usize].clone()).or_insert(0) += 1;
+    }
+    let events = compact.events.len();
+    let objects = declared.values().sum();
+
+    let index = EventLogIndex::from_compact(compact, tie_break);
+
+    // Objects of every event, for the co-occurrence counts
+    let mut event_objects: HashMap<EventId, Vec<(ObjectId, ObjectTypeId)>> = HashMap::new();
+    for relation in &index.relations {
+        let objects = event_objects.entry(relation.event).or_default();
+        if !objects.contains(&(relation.object, relation.object_type)) {
+            objects.push((relation.object, relation.object_type));
+        }
+    }
+
+    let mut collected: HashMap<ObjectTypeId, Collected> = HashMap::new();
+
+    for group in index.relations_by_object() {
+        let otype = group[0].object_type;
+        let stats = collected.entry(otype).or_default();
+
+        // Remove duplicates based on eid (keep first occurrence)
+        let mut seen_eids: HashSet<EventId> = HashSet::new();
+        let lifecycle: Vec<_> = group.into_iter().filter(|relation| seen_eids.insert(relation.event)).collect();
+        let first = lifecycle[0];
+        let last = lifecycle[lifecycle.len() - 1];
+
+        stats.events_per_object.push(lifecycle.len() as f64);
+        stats.lifecycle_duration.push(waiting_seconds(first, last));
+        *stats.first_activities.entry(index.activity(first.activity).to_string()).or_insert(0) += 1;
+        *stats.last_activities.entry(index.activity(last.activity).to_string()).or_insert(0) += 1;
+
+        let mut companions: HashSet<(ObjectId, ObjectTypeId)> = HashSet::new();
+        for relation in &lifecycle {
+            if stats.activity_events.insert(relation.event) {
+                *stats.activities.entry(index.activity(relation.activity).to_string()).or_insert(0) += 1;
+            }
+            companions.extend(event_objects[&relation.event].iter().filter(|(_, t)| *t != otype));
+        }
+
+        let mut per_type: HashMap<ObjectTypeId, usize> = HashMap::new();
+        for (_, companion_type) in companions {
+            *per_type.entry(companion_type).or_insert(0) += 1;
+        }
+        for (companion_type, count) in per_type {
+            stats.co_occurring.entry(companion_type).or_default().push(count as f64);
+        }
+    }
+
+    let object_types = declared
+        .into_iter()
+        .map(|(object_type, objects)| {
+            let stats = index
+                .object_type_id(&object_type)
+                .and_then(|otype| collected.remove(&otype))
+                .unwrap_or_default();
+            let objects_with_events = stats.events_per_object.len();
+
+            // Objects without events count as zero events
+            let mut events_per_object = stats.events_per_object;
+            events_per_object.resize(objects.max(objects_with_events), 0.0);
+
+            // Objects that never meet the other type count as zero as well
+            let co_occurring_objects = stats
+                .co_occurring
+                .into_iter()
+                .filter_map(|(companion_type, mut counts)| {
+                    counts.resize(objects_with_events, 0.0);
+                    Some((index.object_type(companion_type).to_string(), Distribution::from_values(counts)?))
+                })
+                .collect();
+
+            ObjectTypeStatistics {
+                object_type,
+                objects,
+                objects_with_events,
+                events_per_object: Distribution::from_values(events_per_object),
+                lifecycle_duration: Distribution::from_values(stats.lifecycle_duration),
+                first_activities: stats.first_activities,
+                last_activities: stats.last_activities,
+                activities: stats.activities,
+                co_occurring_objects,
+            }
+        })
+        .collect();
+
+    LogStatistics {
+        events,
+        objects,
+        object_types,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocel_stream;
+    use crate::types::{OCELEvent, OCELObject, OCELRelationship, OCEL};
+
+    #[test]
+    fn totals_and_lifecycles_of_a_small_log() {
+        let start = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap();
+        let event = |id: &str, activity: &str, minutes: i64, objects: &[&str]| OCELEvent {
+            id: id.to_string(),
+            event_type: activity.to_string(),
+            time: start + chrono::Duration::minutes(minutes),
+            attributes: Vec::new(),
+            relationships: objects
+                .iter()
+                .map(|object| OCELRelationship { object_id: object.to_string(), qualifier: String::new() })
+                .collect(),
+        };
+        let object = |id: &str, object_type: &str| OCELObject {
+            id: id.to_string(),
+            object_type: object_type.to_string(),
+            attributes: Vec::new(),
+            relationships: Vec::new(),
+        };
+        // o3 has no events, and e4 only names an undeclared object
+        let ocel = OCEL {
+            event_types: Vec::new(),
+            object_types: Vec::new(),
+            events: vec![
+                event("e0", "place", 0, &["o1", "i1", "i2"]),
+                event("e1", "place", 1, &["o2", "i3"]),
+                event("e2", "pack", 2, &["o1", "i1", "i2"]),
+                event("e3", "ship", 5, &["o1"]),
+                event("e4", "ship", 6, &["x9"]),
+            ],
+            objects: vec![
+                object("o1", "order"),
+                object("o2", "order"),
+                object("o3", "order"),
+                object("i1", "item"),
+                object("i2", "item"),
+                object("i3", "item"),
+            ],
+        };
+        let mut compact = CompactOCEL::new();
+        ocel_stream::feed_ocel(ocel, &mut compact);
+
+        let statistics = get_statistics(compact, &TieBreak::default());
+
+        assert_eq!((statistics.events, statistics.objects), (5, 6));
+        let [items, orders] = &statistics.object_types[..] else {
+            panic!("expected item and order statistics, got {:?}", statistics.object_types);
+        };
+
+        assert_eq!((items.object_type.as_str(), items.objects, items.objects_with_events), ("item", 3, 3));
+        assert_eq!(items.last_activities, BTreeMap::from([("pack".to_string(), 2), ("place".to_string(), 1)]));
+        assert_eq!(items.co_occurring_objects["order"].mean, 1.0);
+
+        assert_eq!((orders.object_type.as_str(), orders.objects, orders.objects_with_events), ("order", 3, 2));
+        // o3 counts as an object without events
+        let events_per_object = orders.events_per_object.unwrap();
+        assert_eq!((events_per_object.min, events_per_object.max), (0.0, 3.0));
+        assert_eq!(orders.lifecycle_duration.unwrap().max, 300.0);
+        assert_eq!(orders.first_activities, BTreeMap::from([("place".to_string(), 2)]));
+        assert_eq!(
+            orders.activities,
+            BTreeMap::from([("pack".to_string(), 1), ("place".to_string(), 2), ("ship".to_string(), 1)])
+        );
+        assert_eq!(orders.co_occurring_objects["item"].mean, 1.5);
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs as stdfs;
+use std::io::{BufReader, Read};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset};
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::types::{OCELEvent, OCELObject, OCELType, OCEL};
+
+/// Receives the parts of an OCEL log one at a time while it is being parsed.
+pub trait OCELSink {
+    fn event_type(&mut self, event_type: OCELType);
+    fn object_type(&mut self, object_type: OCELType);
+    fn event(&mut self, event: OCELEvent);
+    fn object(&mut self, object: OCELObject);
+}
+
+impl OCELSink for OCEL {
+    fn event_type(&mut self, event_type: OCELType) {
+        self.event_types.push(event_type);
+    }
+    fn object_type(&mut self, object_type: OCELType) {
+        self.object_types.push(object_type);
+    }
+    fn event(&mut self, event: OCELEvent) {
+        self.events.push(event);
+    }
+    fn object(&mut self, object: OCELObject) {
+        self.objects.push(object);
+    }
+}
+
+/// Passes everything on to `sink`, keeping only the event and object attributes named in
+/// `keep`, so that a log is held with just the attributes the caller reads.
+pub struct AttributeSelection<'a, S> {
+    pub sink: &'a mut S,
+    pub keep: &'a HashSet<String>,
+}
+
+impl<S: OCELSink> OCELSink for AttributeSelection<'_, S> {
+    fn event_type(&mut self, event_type: OCELType) {
+        self.sink.event_type(event_type);
+    }
+    fn object_type(&mut self, object_type: OCELType) {
+        self.sink.object_type(object_type);
+    }
+    fn event(&mut self, mut event: OCELEvent) {
+        event.attributes.retain(|attribute| self.keep.contains(&attribute.name));
+        self.sink.event(event);
+    }
+    fn object(&mut self, mut object: OCELObject) {
+        object.attributes.retain(|attribute| self.keep.contains(&attribute.name));
+        self.sink.object(object);
+    }
+}
+
+/// Parses an OCEL 2.0 JSON log from `reader` without buffering the whole document;
+/// every event and object is handed to `sink` as soon as it is parsed.
+pub fn stream_ocel_json<R: Read, S: OCELSink>(reader: R, sink: &mut S) -> Result<(), String> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::with_capacity(1 << 20, reader));
+    OCELSeed { sink }.deserialize(&mut deserializer).map_err(|e| e.to_string())?;
+    deserializer.end().map_err(|e| e.to_string())
+}
+
+/// Streams an OCEL 2.0 JSON file into a [`OCEL`].
+pub fn read_ocel_json(path: &Path) -> Result<OCEL, String> {
+    let file = stdfs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut ocel = OCEL { event_types: Vec::new(), object_types: Vec::new(), events: Vec::new(), objects: Vec::new() };
+    stream_ocel_json(file, &mut ocel).map_err(|e| format!("Failed to parse OCEL JSON {}: {}", path.display(), e))?;
+    Ok(ocel)
+}
+
+/// Hands an already loaded log to a sink (for formats that cannot be streamed).
+pub fn feed_ocel<S: OCELSink>(ocel: OCEL, sink: &mut S) {
+    ocel.event_types.into_iter().for_each(|t| sink.event_type(t));
+    ocel.object_types.into_iter().for_each(|t| sink.object_type(t));
+    ocel.objects.into_iter().for_each(|o| sink.object(o));
+    ocel.events.into_iter().for_each(|e| sink.event(e));
+}
+
+struct OCELSeed<'a, S> {
+    sink: &'a mut S,
+}
+
+impl<'de, S: OCELSink> DeserializeSeed<'de> for OCELSeed<'_, S> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, S: OCELSink> Visitor<'de> for OCELSeed<'_, S> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an OCEL 2.0 JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let sink = self.sink;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "eventTypes" => map.next_value_seed(EachSeed::new(|t| sink.event_type(t)))?,
+                "objectTypes" => map.next_value_seed(EachSeed::new(|t| sink.object_type(t)))?,
+                "events" => map.next_value_seed(EachSeed::new(|e| sink.event(e)))?,
+                "objects" => map.next_value_seed(EachSeed::new(|o| sink.object(o)))?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Deserializes a JSON array element by element, passing each element to `f`
+struct EachSeed<T, F> {
+    f: F,
+    marker: PhantomData<T>,
+}
+
+impl<T, F> EachSeed<T, F> {
+    fn new(f: F) -> Self {
+        EachSeed { f, marker: PhantomData }
+    }
+}
+
+impl<'de, T: Deserialize<'de>, F: FnMut(T)> DeserializeSeed<'de> for EachSeed<T, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, F: FnMut(T)> Visitor<'de> for EachSeed<T, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(item) = seq.next_element::<T>()? {
+            (self.f)(item);
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Compact representation
+// ---------------------------------------------------------------------------
+
+/// Object type index of objects that are referenced but never declared
+pub const UNDECLARED: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+pub struct CompactEvent {
+    pub id: Box<str>,
+    /// Index into [`CompactOCEL::event_types`]
+    pub event_type: u32,
+    pub time: DateTime<FixedOffset>,
+    /// (index into [`CompactOCEL::objects`], index into [`CompactOCEL::qualifiers`])
+    pub relationships: Vec<(u32, u32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompactObject {
+    pub id: Box<str>,
+    /// Index into [`CompactOCEL::object_types`], or [`UNDECLARED`]
+    pub object_type: u32,
+    /// O2O relationships, same layout as [`CompactEvent::relationships`]
+    pub relationships: Vec<(u32, u32)>,
+}
+
+/// Memory-lean, indexed view of an OCEL log.
+///
+/// Type names and qualifiers are interned, attributes are dropped and E2O/O2O links point
+/// directly at objects by index. Objects may be referenced before they are declared
+/// (events can precede objects in the file); such objects stay [`UNDECLARED`] until then.
+#[derive(Debug, Default)]
+pub struct CompactOCEL {
+    pub event_types: Vec<String>,
+    pub object_types: Vec<String>,
+    pub qualifiers: Vec<String>,
+    pub events: Vec<CompactEvent>,
+    pub objects: Vec<CompactObject>,
+    event_type_index: HashMap<String, u32>,
+    object_type_index: HashMap<String, u32>,
+    qualifier_index: HashMap<String, u32>,
+    object_index: HashMap<Box<str>, u32>,
+}
+
+fn intern(names: &mut Vec<String>, index: &mut HashMap<String, u32>, name: String) -> u32 {
+    if let Some(&i) = index.get(&name) {
+        return i;
+    }
+    let i = names.len() as u32;
+    names.push(name.clone());
+    index.insert(name, i);
+    i
+}
+
+impl CompactOCEL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn object_slot(&mut self, id: String) -> u32 {
+        if let Some(&i) = self.object_index.get(id.as_str()) {
+            return i;
+        }
+        let i = self.objects.len() as u32;
+        let id: Box<str> = id.into_boxed_str();
+        self.objects.push(CompactObject { id: id.clone(), object_type: UNDECLARED, relationships: Vec::new() });
+        self.object_index.insert(id, i);
+        i
+    }
+
+    /// Keeps the events of the activities `keep_activity` accepts and the declared objects of
+    /// the types `keep_object_type` accepts. Relationships to dropped objects go with them,
+    /// and events left without any object are dropped as well.
+    pub fn retain(&mut self, keep_activity: impl Fn(&str) -> bool, keep_object_type: impl Fn(&str) -> bool) {
+        let kept_types: Vec<bool> = self.object_types.iter().map(|name| keep_object_type(name)).collect();
+        let mut slots: Vec<Option<u32>> = Vec::with_capacity(self.objects.len());
+        let mut objects = Vec::new();
+        for object in std::mem::take(&mut self.objects) {
+            let keep = object.object_type != UNDECLARED && kept_types[object.object_type as usize];
+            slots.push(keep.then_some(objects.len() as u32));
+            if keep {
+                objects.push(object);
+            }
+        }
+
+        // Points the relationships at the new object slots, dropping those to removed objects
+        let relink = |relationships: &mut Vec<(u32, u32)>| {
+            relationships.retain_mut(|(object, _)| match slots[*object as usize] {
+                Some(slot) => {
+                    *object = slot;
+                    true
+                }
+                None => false,
+            });
+        };
+        for object in &mut objects {
+            relink(&mut object.relationships);
+        }
+
+        let kept_activities: Vec<bool> = self.event_types.iter().map(|name| keep_activity(name)).collect();
+        self.events.retain_mut(|event| {
+            if !kept_activities[event.event_type as usize] {
+                return false;
+            }
+            relink(&mut event.relationships);
+            !event.relationships.is_empty()
+        });
+
+        self.object_index = objects.iter().enumerate().map(|(i, object)| (object.id.clone(), i as u32)).collect();
+        self.objects = objects;
+    }
+
+    fn link(&mut self, object_id: String, qualifier: String) -> (u32, u32) {
+        let object = self.object_slot(object_id);
+        let qualifier = intern(&mut self.qualifiers, &mut self.qualifier_index, qualifier);
+        (object, qualifier)
+    }
+}
+
+impl OCELSink for CompactOCEL {
+    fn event_type(&mut self, event_type: OCELType) {
+        intern(&mut self.event_types, &mut self.event_type_index, event_type.name);
+    }
+
+    fn object_type(&mut self, object_type: OCELType) {
+        intern(&mut self.object_types, &mut self.object_type_index, object_type.name);
+    }
+
+    fn event(&mut self, event: OCELEvent) {
+        let event_type = intern(&mut self.event_types, &mut self.event_type_index, event.event_type);
+        let relationships = event
+            .relationships
+            .into_iter()
+            .map(|r| self.link(r.object_id, r.qualifier))
+            .collect();
+        self.events.push(CompactEvent { id: event.id.into_boxed_str(), event_type, time: event.time, relationships });
+    }
+
+    fn object(&mut self, object: OCELObject) {
+        let object_type = intern(&mut self.object_types, &mut self.object_type_index, object.object_type);
+        let relationships: Vec<(u32, u32)> = object
+            .relationships
+            .into_iter()
+            .map(|r| self.link(r.object_id, r.qualifier))
+            .collect();
+        let slot = self.object_slot(object.id) as usize;
+        self.objects[slot].object_type = object_type;
+        self.objects[slot].relationships = relationships;
+    }
+}
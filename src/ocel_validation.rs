@@ -0,0 +1,338 @@
+use std::collections::{HashMap, HashSet};
+use std::fs as stdfs;
+use std::io::BufReader;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ocel_import::{self, OCELFormat};
+use crate::types::{parse_timestamp, OCELRelationship, OCELType};
+
+/// Number of example ids kept per issue
+pub const MAX_SAMPLES: usize = 10;
+
+// Lenient mirror of the OCEL 2.0 JSON layout: timestamps and attribute values are kept
+// as raw JSON so that bad values can be reported instead of failing the whole parse
+#[derive(Deserialize)]
+struct RawOCEL {
+    #[serde(rename = "eventTypes", default)]
+    event_types: Vec<OCELType>,
+    #[serde(rename = "objectTypes", default)]
+    object_types: Vec<OCELType>,
+    #[serde(default)]
+    events: Vec<RawEvent>,
+    #[serde(default)]
+    objects: Vec<RawObject>,
+}
+
+#[derive(Deserialize)]
+struct RawEvent {
+    id: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    time: Value,
+    #[serde(default)]
+    attributes: Vec<RawAttribute>,
+    #[serde(default)]
+    relationships: Vec<OCELRelationship>,
+}
+
+#[derive(Deserialize)]
+struct RawObject {
+    id: String,
+    #[serde(rename = "type")]
+    object_type: String,
+    #[serde(default)]
+    attributes: Vec<RawAttribute>,
+    #[serde(default)]
+    relationships: Vec<OCELRelationship>,
+}
+
+#[derive(Deserialize)]
+struct RawAttribute {
+    name: String,
+    #[serde(default)]
+    value: Value,
+    #[serde(default)]
+    time: Option<Value>,
+}
+
+/// Number of occurrences of one kind of problem, with a few example ids
+#[derive(Debug, Default, Serialize)]
+pub struct IssueSummary {
+    pub count: usize,
+    pub samples: Vec<String>,
+}
+
+impl IssueSummary {
+    fn record(&mut self, sample: impl FnOnce() -> String) {
+        self.count += 1;
+        if self.samples.len() < MAX_SAMPLES {
+            self.samples.push(sample());
+        }
+    }
+}
+
+/// Result of [`validate_ocel_file`]
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    pub format: String,
+    pub events: usize,
+    pub objects: usize,
+    pub undeclared_event_types: IssueSummary,
+    pub undeclared_object_types: IssueSummary,
+    pub dangling_e2o_references: IssueSummary,
+    pub dangling_o2o_references: IssueSummary,
+    pub duplicate_event_ids: IssueSummary,
+    pub duplicate_object_ids: IssueSummary,
+    pub events_without_objects: IssueSummary,
+    pub unparsable_timestamps: IssueSummary,
+    pub undeclared_attributes: IssueSummary,
+    pub attribute_type_mismatches: IssueSummary,
+    /// Errors of the XML, SQLite and OCEL 1.0 importers, which stop at the first bad
+    /// timestamp or reference; the other checks are skipped for such logs
+    pub import_errors: IssueSummary,
+}
+
+impl ValidationReport {
+    pub fn issue_count(&self) -> usize {
+        [
+            &self.undeclared_event_types,
+            &self.undeclared_object_types,
+            &self.dangling_e2o_references,
+            &self.dangling_o2o_references,
+            &self.duplicate_event_ids,
+            &self.duplicate_object_ids,
+            &self.events_without_objects,
+            &self.unparsable_timestamps,
+            &self.undeclared_attributes,
+            &self.attribute_type_mismatches,
+            &self.import_errors,
+        ]
+        .iter()
+        .map(|issue| issue.count)
+        .sum()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.issue_count() == 0
+    }
+}
+
+/// Checks the log stored as `data/{file_name}.*` for problems that would otherwise make
+/// discovery panic or silently drop data.
+///
+/// Returns an error only if the file cannot be found or is not an OCEL log at all.
+pub fn validate_ocel_file(file_name: &str) -> Result<ValidationReport, String> {
+    let path = ocel_import::resolve_ocel_path(file_name)
+        .ok_or_else(|| format!("No OCEL log found for '{}' in data/", file_name))?;
+    let format = ocel_import::detect_format(&path)
+        .ok_or_else(|| format!("Unrecognized OCEL format: {}", path.display()))?;
+
+    let mut report = validate_path(&path, format)?;
+    report.format = format.as_str().to_string();
+    Ok(report)
+}
+
+// JSON logs are parsed leniently, so a parse error means the file is no OCEL log; the other
+// formats only get here through their importers, whose errors are problems of the log
+fn validate_path(path: &Path, format: OCELFormat) -> Result<ValidationReport, String> {
+    match read_raw_ocel(path, format) {
+        Ok(raw) => Ok(validate(&raw)),
+        Err(message) if format != OCELFormat::Json => {
+            let mut report = ValidationReport::default();
+            report.import_errors.record(|| message);
+            Ok(report)
+        }
+        Err(message) => Err(message),
+    }
+}
+
+fn read_raw_ocel(path: &Path, format: OCELFormat) -> Result<RawOCEL, String> {
+    if format == OCELFormat::Json {
+        let file = stdfs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        return serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("Failed to parse OCEL JSON {}: {}", path.display(), e));
+    }
+
+    // The other importers only produce well-typed logs; go through serde like the
+    // conformance conversion does
+    let ocel = ocel_import::import_ocel_path(path)?;
+    let ocel_json = serde_json::to_value(&ocel).map_err(|e| format!("Failed to serialize OCEL: {}", e))?;
+    drop(ocel);
+    serde_json::from_value(ocel_json).map_err(|e| format!("Failed to convert OCEL: {}", e))
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "float",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+// Whether `value` can be read as the declared OCEL attribute type. Missing values are
+// always accepted, and times are strings on disk.
+fn value_matches(declared: &str, value: &Value) -> bool {
+    match (declared, value) {
+        (_, Value::Null) => true,
+        ("string", Value::String(_)) => true,
+        ("time", Value::String(s)) => parse_timestamp(s).is_some(),
+        ("integer", Value::Number(n)) => n.is_i64() || n.is_u64(),
+        ("float", Value::Number(_)) => true,
+        ("boolean", Value::Bool(_)) => true,
+        _ => false,
+    }
+}
+
+fn is_valid_timestamp(time: &Value) -> bool {
+    time.as_str().and_then(parse_timestamp).is_some()
+}
+
+fn check_attributes(
+    report: &mut ValidationReport,
+    owner: &str,
+    type_name: &str,
+    declared: Option<&HashMap<&str, &str>>,
+    attributes: &[RawAttribute],
+) {
+    for attribute in attributes {
+        if let Some(time) = attribute.time.as_ref().filter(|time| !is_valid_timestamp(time)) {
+            report.unparsable_timestamps.record(|| format!("{} attribute '{}': {}", owner, attribute.name, time));
+        }
+
+        // Attributes of undeclared types are already covered by the type check
+        let Some(declared) = declared else {
+            continue;
+        };
+        match declared.get(attribute.name.as_str()) {
+            None => report
+                .undeclared_attributes
+                .record(|| format!("{}: '{}' is not declared for type '{}'", owner, attribute.name, type_name)),
+            Some(value_type) if !value_matches(value_type, &attribute.value) => {
+                report.attribute_type_mismatches.record(|| {
+                    format!(
+                        "{}: '{}' declared {}, got {} {}",
+                        owner,
+                        attribute.name,
+                        value_type,
+                        value_kind(&attribute.value),
+                        attribute.value
+                    )
+                })
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+fn declared_attributes(types: &[OCELType]) -> HashMap<&str, HashMap<&str, &str>> {
+    types
+        .iter()
+        .map(|t| {
+            let attributes = t.attributes.iter().map(|a| (a.name.as_str(), a.value_type.as_str())).collect();
+            (t.name.as_str(), attributes)
+        })
+        .collect()
+}
+
+fn validate(ocel: &RawOCEL) -> ValidationReport {
+    let mut report = ValidationReport {
+        events: ocel.events.len(),
+        objects: ocel.objects.len(),
+        ..Default::default()
+    };
+
+    let event_types = declared_attributes(&ocel.event_types);
+    let object_types = declared_attributes(&ocel.object_types);
+
+    let mut object_ids: HashSet<&str> = HashSet::new();
+    for object in &ocel.objects {
+        if !object_ids.insert(object.id.as_str()) {
+            report.duplicate_object_ids.record(|| object.id.clone());
+        }
+    }
+
+    for object in &ocel.objects {
+        let declared = object_types.get(object.object_type.as_str());
+        if declared.is_none() {
+            report
+                .undeclared_object_types
+                .record(|| format!("{} (type '{}')", object.id, object.object_type));
+        }
+        check_attributes(&mut report, &format!("object {}", object.id), &object.object_type, declared, &object.attributes);
+
+        for relationship in &object.relationships {
+            if !object_ids.contains(relationship.object_id.as_str()) {
+                report
+                    .dangling_o2o_references
+                    .record(|| format!("{} -> {}", object.id, relationship.object_id));
+            }
+        }
+    }
+
+    let mut event_ids: HashSet<&str> = HashSet::new();
+    for event in &ocel.events {
+        if !event_ids.insert(event.id.as_str()) {
+            report.duplicate_event_ids.record(|| event.id.clone());
+        }
+
+        let declared = event_types.get(event.event_type.as_str());
+        if declared.is_none() {
+            report
+                .undeclared_event_types
+                .record(|| format!("{} (type '{}')", event.id, event.event_type));
+        }
+
+        if !is_valid_timestamp(&event.time) {
+            report.unparsable_timestamps.record(|| format!("event {}: {}", event.id, event.time));
+        }
+        check_attributes(&mut report, &format!("event {}", event.id), &event.event_type, declared, &event.attributes);
+
+        let mut has_object = false;
+        for relationship in &event.relationships {
+            if object_ids.contains(relationship.object_id.as_str()) {
+                has_object = true;
+            } else {
+                report
+                    .dangling_e2o_references
+                    .record(|| format!("{} -> {}", event.id, relationship.object_id));
+            }
+        }
+        // Events whose objects are all dangling end up without objects in discovery as well
+        if !has_object {
+            report.events_without_objects.record(|| event.id.clone());
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn importer_errors_end_up_in_the_report() {
+        let path = std::env::temp_dir().join(format!("validate-{}-legacy.jsonocel", std::process::id()));
+        let log = r#"{
+            "ocel:global-log": {"ocel:object-types": ["order"]},
+            "ocel:events": {"e1": {"ocel:activity": "place order", "ocel:timestamp": "yesterday", "ocel:omap": ["o1"]}},
+            "ocel:objects": {"o1": {"ocel:type": "order"}}
+        }"#;
+        stdfs::write(&path, log).unwrap();
+        let report = validate_path(&path, OCELFormat::LegacyJson);
+        stdfs::remove_file(&path).unwrap();
+
+        let report = report.unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.import_errors.count, 1);
+        assert!(report.import_errors.samples[0].contains("yesterday"));
+    }
+}
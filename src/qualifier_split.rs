@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{OCELObject, OCELRelationship, OCELType, OCEL};
+
+/// Object types whose E2O qualifiers are treated as separate object types.
+///
+/// With `employee` split, an employee related to one event as `creator` and to another as
+/// `approver` takes part in the log as two objects, `emp1 (creator)` of type
+/// `employee (creator)` and `emp1 (approver)` of type `employee (approver)`. The interaction
+/// patterns, DFGs and traces then treat each role on its own. Relationships without a
+/// qualifier keep the original object, and `*` splits every object type.
+#[derive(Debug, Clone, Default)]
+pub struct QualifierSplit {
+    pub object_types: HashSet<String>,
+}
+
+/// Name of the object type (or object id) standing for `name` in the role `qualifier`
+pub fn role_name(name: &str, qualifier: &str) -> String {
+    format!("{} ({})", name, qualifier)
+}
+
+impl QualifierSplit {
+    /// Parses the comma separated `split_qualifiers` query parameter.
+    pub fn from_param(object_types: Option<&str>) -> Self {
+        QualifierSplit {
+            object_types: object_types
+                .unwrap_or("")
+                .split(',')
+                .map(|object_type| object_type.trim().to_string())
+                .filter(|object_type| !object_type.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.object_types.is_empty()
+    }
+
+    fn splits(&self, object_type: &str) -> bool {
+        self.object_types.contains("*") || self.object_types.contains(object_type)
+    }
+
+    /// Rewrites the E2O relationships of split object types to per-role objects.
+    ///
+    /// Role objects copy the attributes and O2O relationships of their original object, and
+    /// O2O relationships pointing at a split object point at each of its roles. The original
+    /// object is kept only if some event still refers to it without a qualifier.
+    pub fn apply(&self, ocel: OCEL) -> OCEL {
+        if self.is_empty() {
+            return ocel;
+        }
+
+        let object_types: HashMap<String, String> = ocel
+            .objects
+            .iter()
+            .map(|object| (object.id.clone(), object.object_type.clone()))
+            .collect();
+
+        // original object -> its roles, in order of first appearance
+        let mut roles: HashMap<String, Vec<String>> = HashMap::new();
+        let mut unqualified: HashSet<String> = HashSet::new();
+        let mut events = ocel.events;
+        for event in &mut events {
+            for relationship in &mut event.relationships {
+                let Some(object_type) = object_types.get(&relationship.object_id) else {
+                    continue;
+                };
+                if !self.splits(object_type) {
+                    continue;
+                }
+                if relationship.qualifier.is_empty() {
+                    unqualified.insert(relationship.object_id.clone());
+                    continue;
+                }
+                let object_roles = roles.entry(relationship.object_id.clone()).or_default();
+                if !object_roles.contains(&relationship.qualifier) {
+                    object_roles.push(relationship.qualifier.clone());
+                }
+                relationship.object_id = role_name(&relationship.object_id, &relationship.qualifier);
+            }
+        }
+
+        // O2O targets are replaced by every object standing for them
+        let expand = |object_id: &str| -> Vec<String> {
+            let Some(object_roles) = roles.get(object_id) else {
+                return vec![object_id.to_string()];
+            };
+            let mut ids: Vec<String> = object_roles.iter().map(|role| role_name(object_id, role)).collect();
+            if unqualified.contains(object_id) {
+                ids.push(object_id.to_string());
+            }
+            ids
+        };
+
+        let mut objects = Vec::with_capacity(ocel.objects.len());
+        // (role type, original type)
+        let mut role_types: Vec<(String, String)> = Vec::new();
+        for mut object in ocel.objects {
+            object.relationships = object
+                .relationships
+                .into_iter()
+                .flat_map(|relationship| {
+                    expand(&relationship.object_id).into_iter().map(move |object_id| {
+                        OCELRelationship { object_id, qualifier: relationship.qualifier.clone() }
+                    })
+                })
+                .collect();
+
+            let Some(object_roles) = roles.get(&object.id) else {
+                objects.push(object);
+                continue;
+            };
+            for role in object_roles {
+                let object_type = role_name(&object.object_type, role);
+                if !role_types.iter().any(|(role_type, _)| *role_type == object_type) {
+                    role_types.push((object_type.clone(), object.object_type.clone()));
+                }
+                objects.push(OCELObject {
+                    id: role_name(&object.id, role),
+                    object_type,
+                    attributes: object.attributes.clone(),
+                    relationships: object.relationships.clone(),
+                });
+            }
+            if unqualified.contains(&object.id) {
+                objects.push(object);
+            }
+        }
+
+        let mut object_types = ocel.object_types;
+        for (role_type, base) in role_types {
+            // Role types inherit the attribute declarations of their object type
+            let attributes = object_types
+                .iter()
+                .find(|t| t.name == base)
+                .map(|t| t.attributes.clone())
+                .unwrap_or_default();
+            object_types.push(OCELType { name: role_type, attributes });
+        }
+
+        OCEL { event_types: ocel.event_types, object_types, events, objects }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OCELEvent, OCELTypeAttribute};
+
+    #[test]
+    fn roles_are_named_after_object_and_qualifier() {
+        let relationship = |object_id: &str, qualifier: &str| OCELRelationship {
+            object_id: object_id.to_string(),
+            qualifier: qualifier.to_string(),
+        };
+        let event = |id: &str, qualifier: &str| OCELEvent {
+            id: id.to_string(),
+            event_type: "approve".to_string(),
+            time: "2024-01-01T00:00:00Z".parse().unwrap(),
+            attributes: Vec::new(),
+            relationships: vec![relationship("o1", "order"), relationship("emp1", qualifier)],
+        };
+        let object = |id: &str, object_type: &str, relationships: Vec<OCELRelationship>| OCELObject {
+            id: id.to_string(),
+            object_type: object_type.to_string(),
+            attributes: Vec::new(),
+            relationships,
+        };
+        let employee = OCELType {
+            name: "employee".to_string(),
+            attributes: vec![OCELTypeAttribute { name: "team".to_string(), value_type: "string".to_string() }],
+        };
+        let ocel = OCEL {
+            event_types: Vec::new(),
+            object_types: vec![employee],
+            events: vec![event("e1", "creator"), event("e2", "approver"), event("e3", "")],
+            objects: vec![object("o1", "order", vec![relationship("emp1", "handled by")]), object("emp1", "employee", Vec::new())],
+        };
+
+        let ocel = QualifierSplit::from_param(Some("employee")).apply(ocel);
+
+        let related: Vec<&str> = ocel.events.iter().map(|event| event.relationships[1].object_id.as_str()).collect();
+        assert_eq!(related, ["emp1 (creator)", "emp1 (approver)", "emp1"]);
+        let objects: Vec<(&str, &str)> =
+            ocel.objects.iter().map(|object| (object.id.as_str(), object.object_type.as_str())).collect();
+        assert_eq!(
+            objects,
+            [
+                ("o1", "order"),
+                ("emp1 (creator)", "employee (creator)"),
+                ("emp1 (approver)", "employee (approver)"),
+                ("emp1", "employee"),
+            ]
+        );
+        // O2O links to the employee reach every object standing for it
+        let linked: Vec<&str> = ocel.objects[0].relationships.iter().map(|r| r.object_id.as_str()).collect();
+        assert_eq!(linked, ["emp1 (creator)", "emp1 (approver)", "emp1"]);
+        // Role types inherit the attribute declarations of their object type
+        let types: Vec<(&str, usize)> = ocel.object_types.iter().map(|t| (t.name.as_str(), t.attributes.len())).collect();
+        assert_eq!(types, [("employee", 1), ("employee (creator)", 1), ("employee (approver)", 1)]);
+        // Blank entries of the parameter are ignored
+        assert_eq!(QualifierSplit::from_param(Some("order, ")).object_types, HashSet::from(["order".to_string()]));
+    }
+}
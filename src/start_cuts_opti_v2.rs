@@ -1,23 +1,15 @@
-use crate::best_exclusive_cut::best_exclusive_cut;
-use crate::best_parallel_cut::best_parallel_cut;
-use crate::best_parallel_cut_exhaustive::best_parallel_cut_exhaustive;
-use crate::best_parallel_cut_v2::best_parallel_cut_v2;
-use crate::best_parallel_cut_v3::best_parallel_cut_v3;
-use crate::best_parallel_cut_v4::best_parallel_cut_v4;
-use crate::best_redo_cuts::best_redo_cut;
-use crate::best_sequence_cut::best_sequence_cut;
-use crate::best_sequence_cut_v2;
 use crate::cost_to_add::cost_of_adding_edge;
 use crate::cost_to_cut::is_reachable;
 use crate::cost_to_cut::to_be_non_reachable;
-use crate::good_cuts::best_possible_sequence_cut;
+use crate::cut_finders::{CutInput, CutStrategies};
+use crate::fall_throughs::{self, SubTrace};
+use crate::node_ids::assign_node_ids;
 use crate::types::CutSuggestion;
 use crate::types::CutSuggestionsList;
-use crate::types::{ProcessForest, TreeNode};
+use crate::types::{ObjectTrace, ProcessForest, TreeNode};
 use itertools::Itertools;
 use log::info;
 use std::collections::{HashMap, HashSet, VecDeque};
-use uuid::Uuid;
 
 pub fn find_cuts_start(
     dfg: &HashMap<(String, String), usize>,
@@ -25,125 +17,168 @@ pub fn find_cuts_start(
     start_activities: &HashSet<String>,
     end_activities: &HashSet<String>,
 ) -> ProcessForest {
+    with_node_ids(find_cuts(dfg, all_activities, start_activities, end_activities, None))
+}
+
+/// Like [`find_cuts_start`], with the object traces of the log. The traces are split along
+/// every cut; objects that skip a sub-process make it optional (`exclusive(..., tau)`), and
+/// the activity once per trace fall-through becomes available.
+pub fn find_cuts_start_with_traces(
+    dfg: &HashMap<(String, String), usize>,
+    all_activities: &HashSet<String>,
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+    traces: &[ObjectTrace],
+) -> ProcessForest {
+    let traces = fall_throughs::sub_traces(traces, all_activities);
+    with_node_ids(find_cuts(dfg, all_activities, start_activities, end_activities, Some(&traces)))
+}
+
+/// The branches below a cut chosen by hand for a flower, one forest per set of the cut, each
+/// discovered on the object traces split along the cut. Objects that skip the flower as a
+/// whole are left out, discovery already made the flower optional for them.
+pub fn find_cuts_below_cut(
+    dfg: &HashMap<(String, String), usize>,
+    cut_type: &str,
+    sets: &[HashSet<String>],
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+    traces: &[ObjectTrace],
+) -> Vec<ProcessForest> {
+    let all_activities: HashSet<String> = sets.iter().flatten().cloned().collect();
+    let traces = fall_throughs::without_empty_traces(&fall_throughs::sub_traces(traces, &all_activities));
+    fall_throughs::split(cut_type, &traces, sets)
+        .iter()
+        .zip(sets)
+        .map(|(sub_log, set)| with_node_ids(find_cuts(dfg, set, start_activities, end_activities, Some(sub_log))))
+        .collect()
+}
+
+fn find_cuts(
+    dfg: &HashMap<(String, String), usize>,
+    all_activities: &HashSet<String>,
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+    traces: Option<&[SubTrace]>,
+) -> ProcessForest {
+    // Base case: objects that skip every activity make the whole sub-process optional
+    if let Some(traces) = traces.filter(|traces| fall_throughs::has_empty_traces(traces)) {
+        info!("Empty traces, {:?} can be skipped", all_activities);
+        let non_empty = fall_throughs::without_empty_traces(traces);
+        return optional(find_cuts(dfg, all_activities, start_activities, end_activities, Some(&non_empty)));
+    }
+
     let mut forest = Vec::new();
 
-    let activities: Vec<String> = all_activities.clone().into_iter().collect();
+    let mut activities: Vec<String> = all_activities.clone().into_iter().collect();
+    activities.sort();
     let n = activities.len();
 
     if n == 1 {
         // Base case: single activity, create a leaf node
-        let node = TreeNode {
-            id: Uuid::new_v4().to_string(),
-            label: activities[0].clone(),
-            children: Vec::new(),
-        };
-        forest.push(node);
+        forest.push(tree_node(&activities[0], Vec::new()));
         return forest;
     }
 
-    let filtered_dfg = filter_keep_dfg(&dfg, &all_activities);
+    let filtered_dfg = filter_keep_dfg(dfg, all_activities);
     let (start_activities, end_activities) =
-        get_start_and_end_activities(&dfg, &all_activities, &start_activities, &end_activities);
+        get_start_and_end_activities(dfg, all_activities, start_activities, end_activities);
 
     // ----- perform cuts--------
 
-    let (excl_set1, excl_set2) = find_exclusive_choice_cut(&filtered_dfg, &all_activities);
-    let (is_exclusive_possible, _excl_failures) = exclusive_cut_condition_check(&filtered_dfg, &excl_set1, &excl_set2);
+    if let Some((label, sets)) = find_cut(&filtered_dfg, all_activities, &start_activities, &end_activities) {
+        let sub_logs = traces.map(|traces| fall_throughs::split(label, traces, &sets));
+        // The traces show skipped branches as empty sub-traces, per object type; without them
+        // the DFG has to tell, which only works for sequences
+        let skippable = match (label, traces) {
+            ("sequence", None) => skippable_branches(&filtered_dfg, &sets, &start_activities, &end_activities),
+            _ => vec![false; sets.len()],
+        };
+        let mut children = Vec::new();
+        for (i, set) in sets.iter().enumerate() {
+            let sub_log = sub_logs.as_ref().map(|sub_logs| sub_logs[i].as_slice());
+            let child = find_cuts(dfg, set, &start_activities, &end_activities, sub_log);
+            children.extend(if skippable[i] { optional(child) } else { child });
+        }
+        forest.push(tree_node(label, children));
+        return forest;
+    }
+
+    info!(
+        "No further cuts found for the current set of activities: {:?}",
+        all_activities
+    );
+
+    if let Some(node) = fall_through(dfg, &filtered_dfg, &activities, &start_activities, &end_activities, traces) {
+        forest.push(node);
+        return forest;
+    }
+
+    // If no fall-through applies either, create a flower node with disjoint activities as children
+    let flower_children = activities.iter().map(|activity| tree_node(activity, Vec::new())).collect();
+    forest.push(tree_node("flower", flower_children));
+
+    return forest;
+
+}
+
+// Operator label and the activity sets of the branches of a cut
+type Cut = (&'static str, Vec<HashSet<String>>);
+
+// First cut the DFG restricted to `all_activities` allows, trying exclusive, sequence,
+// parallel and redo in that order, widened to its maximal partition
+fn find_cut(
+    filtered_dfg: &HashMap<(String, String), usize>,
+    all_activities: &HashSet<String>,
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+) -> Option<Cut> {
+    let (excl_set1, excl_set2) = find_exclusive_choice_cut(filtered_dfg, all_activities);
+    let (is_exclusive_possible, _excl_failures) = exclusive_cut_condition_check(filtered_dfg, &excl_set1, &excl_set2);
     if (!excl_set1.is_empty()
         && !excl_set2.is_empty()
         && is_exclusive_possible)
     {
         info!("Exclusive cut found: {:?} (X) {:?}", excl_set1, excl_set2);
-        let mut node = TreeNode {
-            id: Uuid::new_v4().to_string(),
-            label: "exclusive".to_string(),
-            children: Vec::new(),
-        };
-        node.children.extend(find_cuts_start(
-            &dfg,
-            &excl_set1,
-            &start_activities,
-            &end_activities,
-        ));
-        node.children.extend(find_cuts_start(
-            &dfg,
-            &excl_set2,
-            &start_activities,
-            &end_activities,
-        ));
-        forest.push(node);
-        return forest;
+        let sets = maximal_partition("exclusive", filtered_dfg, all_activities, excl_set1, excl_set2, start_activities, end_activities);
+        return Some(("exclusive", sets));
     }
 
-    let (seq_set1, seq_set2) = find_sequence_cut(&filtered_dfg, &all_activities);
-    let (is_sequence_possible, _seq_failures) = sequence_cut_condition_check(&filtered_dfg, &seq_set1, &seq_set2);
+    let (seq_set1, seq_set2) = find_sequence_cut(filtered_dfg, all_activities);
+    let (is_sequence_possible, _seq_failures) = sequence_cut_condition_check(filtered_dfg, &seq_set1, &seq_set2);
     if (!seq_set1.is_empty()
         && !seq_set2.is_empty()
         && is_sequence_possible)
     {
         info!("Sequence cut found: {:?} (->) {:?}", seq_set1, seq_set2);
-        let mut node = TreeNode {
-            id: Uuid::new_v4().to_string(),
-            label: "sequence".to_string(),
-            children: Vec::new(),
-        };
-        node.children.extend(find_cuts_start(
-            &dfg,
-            &seq_set1,
-            &start_activities,
-            &end_activities,
-        ));
-        node.children.extend(find_cuts_start(
-            &dfg,
-            &seq_set2,
-            &start_activities,
-            &end_activities,
-        ));
-        forest.push(node);
-        return forest;
+        let sets = maximal_partition("sequence", filtered_dfg, all_activities, seq_set1, seq_set2, start_activities, end_activities);
+        return Some(("sequence", sets));
     }
 
-    let (is_parallel, para_set1, para_set2) = find_parallel_cut(&filtered_dfg, &all_activities);
-    let (parallel_condition_passed, _parallel_failures) = parallel_cut_condition_check(&filtered_dfg, &para_set1, &para_set2, &start_activities, &end_activities);
+    let (is_parallel, para_set1, para_set2) = find_parallel_cut(filtered_dfg, all_activities);
+    let (parallel_condition_passed, _parallel_failures) = parallel_cut_condition_check(filtered_dfg, &para_set1, &para_set2, start_activities, end_activities);
     if (is_parallel
         && !para_set1.is_empty()
         && !para_set2.is_empty()
         && parallel_condition_passed)
     {
         info!("Parallel cut found: {:?} (||) {:?}", para_set1, para_set2);
-        let mut node = TreeNode {
-            id: Uuid::new_v4().to_string(),
-            label: "parallel".to_string(),
-            children: Vec::new(),
-        };
-        node.children.extend(find_cuts_start(
-            &dfg,
-            &para_set1,
-            &start_activities,
-            &end_activities,
-        ));
-        node.children.extend(find_cuts_start(
-            &dfg,
-            &para_set2,
-            &start_activities,
-            &end_activities,
-        ));
-        forest.push(node);
-        return forest;
+        let sets = maximal_partition("parallel", filtered_dfg, all_activities, para_set1, para_set2, start_activities, end_activities);
+        return Some(("parallel", sets));
     }
 
     let (is_redo, redo_set1, redo_set2) = find_redo_cut(
-        &filtered_dfg,
-        &all_activities,
-        &start_activities,
-        &end_activities,
+        filtered_dfg,
+        all_activities,
+        start_activities,
+        end_activities,
     );
     let (redo_condition_passed, _redo_failures) = redo_cut_condition_check(
-        &filtered_dfg,
+        filtered_dfg,
         &redo_set1,
         &redo_set2,
-        &start_activities,
-        &end_activities,
+        start_activities,
+        end_activities,
     );
     if (is_redo
         && !redo_set2.is_empty()
@@ -151,62 +186,212 @@ pub fn find_cuts_start(
         && redo_condition_passed)
     {
         info!("Redo cut found: {:?} (R) {:?}", redo_set1, redo_set2);
-        let mut node = TreeNode {
-            id: Uuid::new_v4().to_string(),
-            label: "redo".to_string(),
-            children: Vec::new(),
-        };
-        node.children.extend(find_cuts_start(
-            &dfg,
-            &redo_set1,
-            &start_activities,
-            &end_activities,
-        ));
-        node.children.extend(find_cuts_start(
-            &dfg,
-            &redo_set2,
-            &start_activities,
-            &end_activities,
-        ));
-        forest.push(node);
-        return forest;
+        return Some(("redo", vec![redo_set1, redo_set2]));
     }
 
-    info!(
-        "No further cuts found for the current set of activities: {:?}",
-        all_activities
-    );
+    None
+}
 
-    // If no valid cuts are found, create a flower node with disjoint activities as children
-    let mut flower_node = TreeNode {
-        id: Uuid::new_v4().to_string(),
-        label: "flower".to_string(),
-        children: Vec::new(),
+/// Widens a binary cut to the maximal partition of its type, as the Inductive Miner does:
+/// every connected component of an exclusive choice, every group of a sequence and every
+/// concurrent group of a parallel cut becomes its own branch. `dfg` is the DFG the cut holds
+/// on; redo cuts and cuts that cannot be widened keep their two sets.
+fn maximal_partition(
+    cut_type: &str,
+    dfg: &HashMap<(String, String), usize>,
+    all_activities: &HashSet<String>,
+    set1: HashSet<String>,
+    set2: HashSet<String>,
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+) -> Vec<HashSet<String>> {
+    let partition = match cut_type {
+        "exclusive" => Some(connected_components(dfg, all_activities)),
+        "sequence" => sequence_partition(dfg, all_activities),
+        "parallel" => parallel_partition(dfg, all_activities, start_activities, end_activities),
+        _ => None,
     };
+    match partition {
+        Some(sets) if sets.len() > 2 => sets,
+        _ => vec![set1, set2],
+    }
+}
 
-    // Add all activities as children of the flower node
-    for activity in activities {
-        let child_node = TreeNode {
-            id: Uuid::new_v4().to_string(),
-            label: activity,
-            children: Vec::new(),
-        };
-        flower_node.children.push(child_node);
+// Branches of a sequence cut the DFG lets the process bypass: a start activity lies after the
+// branch, an end activity before it, or an edge jumps over it
+fn skippable_branches(
+    dfg: &HashMap<(String, String), usize>,
+    sets: &[HashSet<String>],
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+) -> Vec<bool> {
+    let position = |activity: &String| sets.iter().position(|set| set.contains(activity));
+    (0..sets.len())
+        .map(|i| {
+            sets[i + 1..].iter().any(|set| !set.is_disjoint(start_activities))
+                || sets[..i].iter().any(|set| !set.is_disjoint(end_activities))
+                || dfg.keys().any(|(from, to)| {
+                    matches!((position(from), position(to)), (Some(a), Some(b)) if a < i && b > i)
+                })
+        })
+        .collect()
+}
+
+// exclusive(forest, tau), unless the forest already is such a choice
+fn optional(mut forest: ProcessForest) -> ProcessForest {
+    let is_optional = forest.len() == 1
+        && forest[0].label == "exclusive"
+        && forest[0].children.iter().any(|child| child.label == "tau");
+    if is_optional {
+        return forest;
     }
+    forest.push(tree_node("tau", Vec::new()));
+    vec![tree_node("exclusive", forest)]
+}
 
-    // Add the flower node to the forest
-    forest.push(flower_node);
+// Inductive Miner fall-throughs, tried in order when no cut applies:
+//
+// - activity once per trace: an activity every related object performs exactly once
+//   -> parallel(activity, rest)
+// - activity concurrent: an activity whose removal lets the rest be cut -> parallel(activity, rest)
+// - strict tau loop: the end -> start edges are removed -> redo(rest, tau)
+// - tau loop: every edge into a start activity is removed -> redo(rest, tau)
+//
+// The first one needs the traces and is skipped without them.
+fn fall_through(
+    dfg: &HashMap<(String, String), usize>,
+    filtered_dfg: &HashMap<(String, String), usize>,
+    activities: &[String],
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+    traces: Option<&[SubTrace]>,
+) -> Option<TreeNode> {
+    let all_activities: HashSet<String> = activities.iter().cloned().collect();
+
+    let once_per_trace = traces.and_then(|traces| {
+        fall_throughs::activity_once_per_trace(traces, activities).map(|activity| (traces, activity))
+    });
+    if let Some((traces, activity)) = once_per_trace {
+        info!("Fall-through activity once per trace: {}", activity);
+        let rest: HashSet<String> = all_activities.iter().filter(|a| **a != activity).cloned().collect();
+        let (activity_traces, rest_traces) = fall_throughs::split_off_activity(traces, &activity, &rest);
+        let mut children = find_cuts(dfg, &HashSet::from([activity]), start_activities, end_activities, Some(&activity_traces));
+        children.extend(find_cuts(dfg, &rest, start_activities, end_activities, Some(&rest_traces)));
+        return Some(tree_node("parallel", children));
+    }
 
-    return forest;
+    if activities.len() >= 3 {
+        for activity in activities {
+            let reduced_dfg = fall_throughs::dfg_without_activity(dfg, activity);
+            let rest: HashSet<String> = all_activities.iter().filter(|a| *a != activity).cloned().collect();
+
+            // Without the activity, what followed it can start the rest and what preceded it can end it
+            let mut rest_start = start_activities.clone();
+            let mut rest_end = end_activities.clone();
+            for (from, to) in filtered_dfg.keys() {
+                if from == activity && start_activities.contains(activity) {
+                    rest_start.insert(to.clone());
+                }
+                if to == activity && end_activities.contains(activity) {
+                    rest_end.insert(from.clone());
+                }
+            }
+            let (local_start, local_end) = get_start_and_end_activities(&reduced_dfg, &rest, &rest_start, &rest_end);
+            if find_cut(&filter_keep_dfg(&reduced_dfg, &rest), &rest, &local_start, &local_end).is_none() {
+                continue;
+            }
+
+            info!("Fall-through activity concurrent: {} (||) {:?}", activity, rest);
+            let (activity_traces, rest_traces) = match traces {
+                Some(traces) => {
+                    let (activity_traces, rest_traces) = fall_throughs::split_off_activity(traces, activity, &rest);
+                    (Some(activity_traces), Some(rest_traces))
+                }
+                None => (None, None),
+            };
+            let mut children = find_cuts(dfg, &HashSet::from([activity.clone()]), start_activities, end_activities, activity_traces.as_deref());
+            children.extend(find_cuts(&reduced_dfg, &rest, &rest_start, &rest_end, rest_traces.as_deref()));
+            return Some(tree_node("parallel", children));
+        }
+    }
+
+    // Self loops are left to add_self_loops, which wraps single activities in their own redo
+    let strict_loop_edges: Vec<(String, String)> = filtered_dfg
+        .keys()
+        .filter(|(from, to)| from != to && end_activities.contains(from) && start_activities.contains(to))
+        .cloned()
+        .collect();
+    if !strict_loop_edges.is_empty() {
+        info!("Fall-through strict tau loop: {:?}", strict_loop_edges);
+        return Some(tau_loop(dfg, &all_activities, start_activities, end_activities, &strict_loop_edges, traces));
+    }
+
+    let loop_edges: Vec<(String, String)> = filtered_dfg
+        .keys()
+        .filter(|(from, to)| from != to && start_activities.contains(to))
+        .cloned()
+        .collect();
+    if !loop_edges.is_empty() {
+        info!("Fall-through tau loop: {:?}", loop_edges);
+        // The activities before a restart end an iteration of the body
+        let mut body_end = end_activities.clone();
+        body_end.extend(loop_edges.iter().map(|(from, _)| from.clone()));
+        return Some(tau_loop(dfg, &all_activities, start_activities, &body_end, &loop_edges, traces));
+    }
+
+    None
+}
+
+// redo(body, tau), the body discovered on the DFG without `loop_edges`
+fn tau_loop(
+    dfg: &HashMap<(String, String), usize>,
+    all_activities: &HashSet<String>,
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+    loop_edges: &[(String, String)],
+    traces: Option<&[SubTrace]>,
+) -> TreeNode {
+    let mut body_dfg = dfg.clone();
+    for edge in loop_edges {
+        body_dfg.remove(edge);
+    }
+    let body_traces = traces.map(|traces| {
+        fall_throughs::split_traces_between(traces, |from, to| {
+            loop_edges.iter().any(|(a, b)| a == from && b == to)
+        })
+    });
+    let mut children = find_cuts(&body_dfg, all_activities, start_activities, end_activities, body_traces.as_deref());
+    children.push(tree_node("tau", Vec::new()));
+    tree_node("redo", children)
+}
 
+// Nodes are built without ids; the finished forest gets ids derived from its content
+fn with_node_ids(mut forest: ProcessForest) -> ProcessForest {
+    assign_node_ids(&mut forest);
+    forest
 }
 
+fn tree_node(label: &str, children: Vec<TreeNode>) -> TreeNode {
+    TreeNode {
+        id: String::new(),
+        label: label.to_string(),
+        children,
+    }
+}
+
+/// Suggests the cheapest exclusive, sequence, parallel and redo cut of `all_activities`, each
+/// found by the finder `strategies` chooses for that cut type.
+///
+/// The cut finders go through activities, candidate partitions and edges in name order, so
+/// ties between equally good cuts break the same way every run and the suggestions (and the
+/// edges they add or remove) can be compared across runs.
 pub fn find_best_possible_cuts(
     dfg: &HashMap<(String, String), usize>,
     all_activities: &HashSet<String>,
     start_activities: &HashSet<String>,
     end_activities: &HashSet<String>,
     cost_to_add_edges: &HashMap<(String, String), f64>,
+    strategies: &CutStrategies,
 ) -> CutSuggestionsList{
 
     println!("Finding best possible cuts for all activities: {:?}", all_activities);
@@ -226,162 +411,51 @@ pub fn find_best_possible_cuts(
     let mut cuts: Vec<CutSuggestion> = Vec::new();
 
 
-    println!("Checking for best possible exclusive cut...");
-    let (be_min_cost, be_cut_edges, be_set1, be_set2, be_new_dfg) = best_exclusive_cut(&filtered_dfg, &all_activities);
-    if (be_set1.is_empty() || be_set2.is_empty()){
-        info!("Best exclusive cut possible condition failed: one of the sets is empty");
-    } else {
-        let (is_exclusive, be_failures) = exclusive_cut_condition_check(&be_new_dfg, &be_set1, &be_set2);
-        if (!is_exclusive) {
-            // I dont think this is possible, but just in case
-            // because, for 'a' in set1, and 'b' in set2, we would definitely have a forced sequence cut
-            info!("Exclusive cut condition failed for sets: {:?} and {:?}", be_set1, be_set2);
-            for (a, b, r1, r2) in be_failures {
-                info!("Condition failure: {} -> {} (reachable: {}, {})", a, b, r1, r2);
-            }
-        } else {
-            println!("\n=== BEST EXCLUSIVE CUT RESULTS ===");
-            println!("Minimum Cost: {}", be_min_cost);
-            println!("Cut Edges: {:?}", be_cut_edges);
-            println!("Set 1: {:?}", be_set1);
-            println!("Set 2: {:?}", be_set2);
-            cuts.push(CutSuggestion {
-                cut_type: "exclusive".to_string(),
-                set1: be_set1,
-                set2: be_set2,
-                edges_to_be_added: Vec::new(),
-                edges_to_be_removed: be_cut_edges,
-                total_cost: be_min_cost,
-            });
-        }
-    }
-
-
-    println!("Checking for best possible sequence cut...");
-    let (
-        bs_min_cost,
-        bs_no_of_cuts,
-        bs_cut_edges,
-        bs_no_of_added_edges,
-        bs_added_edges,
-        bs_set1,
-        bs_set2,
-        bs_new_dfg,
-    ) = best_sequence_cut(&filtered_dfg, &all_activities, &cost_to_add_edges);
-    if bs_set1.is_empty() || bs_set2.is_empty() {
-        info!("Best sequence cut possible condition failed: one of the sets is empty");
-    } else {
-        let (is_sequence, failures) = sequence_cut_condition_check(&bs_new_dfg, &bs_set1, &bs_set2);
-        if !is_sequence {
-            // I dont think this is possible, but just in case
-            // because, for 'a' in set1, and 'b' in set2, we would definitely have a forced sequence cut
-            info!(
-                "Sequence cut condition failed for sets: {:?} and {:?}",
-                bs_set1, bs_set2
-            );
+    let input = CutInput {
+        dfg: &filtered_dfg,
+        activities: all_activities,
+        start_activities: &start_activities,
+        end_activities: &end_activities,
+        cost_to_add_edges,
+        seed: strategies.seed,
+    };
+    for finder in strategies.finders() {
+        let cut_type = finder.cut_type();
+        println!("Checking for best possible {} cut with {}...", cut_type, finder.name());
+        let Some(found) = finder.find_cut(&input) else {
+            info!("Best {} cut possible condition failed: no partition into two non-empty sets", cut_type);
+            continue;
+        };
+        let new_dfg = filter_keep_dfg(&found.new_dfg, all_activities);
+        let (condition_passed, failures) = match cut_type {
+            "exclusive" => exclusive_cut_condition_check(&new_dfg, &found.set1, &found.set2),
+            "sequence" => sequence_cut_condition_check(&new_dfg, &found.set1, &found.set2),
+            "parallel" => parallel_cut_condition_check(&new_dfg, &found.set1, &found.set2, &start_activities, &end_activities),
+            _ => redo_cut_condition_check(&new_dfg, &found.set1, &found.set2, &start_activities, &end_activities),
+        };
+        if !condition_passed {
+            info!("{} cut condition failed for sets: {:?} and {:?}", cut_type, found.set1, found.set2);
             for (a, b, r1, r2) in failures {
-                info!(
-                    "Condition failure: {} -> {} (reachable: {}, {})",
-                    a, b, r1, r2
-                );
-            }
-        } else {
-            println!("\n=== BEST SEQUENCE CUT RESULTS ===");
-            println!("Minimum Cost: {}", bs_min_cost);
-            println!("Number of cut edges: {}", bs_no_of_cuts);
-            println!("Cut Edges: {:?}", bs_cut_edges);
-            println!("Number of added edges: {}", bs_no_of_added_edges);
-            println!("Added Edges: {:?}", bs_added_edges);
-            println!("Set 1: {:?}", bs_set1);
-            println!("Set 2: {:?}", bs_set2);
-            cuts.push(CutSuggestion {
-                cut_type: "sequence".to_string(),
-                set1: bs_set1,
-                set2: bs_set2,
-                edges_to_be_added: bs_added_edges,
-                edges_to_be_removed: bs_cut_edges,
-                total_cost: bs_min_cost,
-            });
-        }
-    }
-
-
-    println!("Checking for best possible parallel cut...");
-    let (
-        bp_min_cost,
-        bp_no_of_added_edges,
-        bp_added_edges,
-        bp_set1,
-        bp_set2,
-        bp_new_dfg,
-    ) = best_parallel_cut_v4(&filtered_dfg, &all_activities, &cost_to_add_edges, &start_activities, &end_activities);
-    if bp_set1.is_empty() || bp_set2.is_empty() {
-        println!("Best parallel cut possible condition failed: one of the sets is empty");
-    } else {
-        let (parallel_condition_passed, _parallel_failures) = parallel_cut_condition_check(&bp_new_dfg, &bp_set1, &bp_set2, &start_activities, &end_activities);
-        if !parallel_condition_passed {
-            println!("Parallel cut condition failed for sets: {:?} and {:?}", bp_set1, bp_set2);
-            for (a, b, r1, r2) in _parallel_failures {
-                println!("Condition failure: {} -> {} (reachable: {}, {})", a, b, r1, r2);
-            }
-        } else {
-            println!("\n=== BEST PARALLEL CUT RESULTS ===");
-            println!("Minimum cost: {}", bp_min_cost);
-            println!("Total Number of Edges to Add: {:?}", bp_no_of_added_edges);
-            println!("Set1: {:?}", bp_set1);
-            println!("Set2: {:?}", bp_set2);
-            cuts.push(CutSuggestion {
-                cut_type: "parallel".to_string(),
-                set1: bp_set1,
-                set2: bp_set2,
-                edges_to_be_added: bp_added_edges,
-                edges_to_be_removed: Vec::new(),
-                total_cost: bp_min_cost,
-            });
-        }
-    }
-
-    println!("Checking for best redo cut...");
-    let (br_is_redo, br_min_cost, br_edges_removed, br_edges_added, br_cost_of_added_edges, br_cost_of_removed_edges, br_set1, br_set2, br_new_dfg) =
-        best_redo_cut(&filtered_dfg, &all_activities, &start_activities, &end_activities, &cost_to_add_edges);
-    if br_is_redo {
-        if br_set1.is_empty() || br_set2.is_empty() {
-            info!("Best redo cut possible condition failed: one of the sets is empty");
-        } else {
-            let (redo_condition_passed, _redo_failures) = redo_cut_condition_check(
-                &br_new_dfg,
-                &br_set1,
-                &br_set2,
-                &start_activities,
-                &end_activities,
-            );
-            if !redo_condition_passed {
-                info!("Redo cut condition failed for sets: {:?} and {:?}", br_set1, br_set2);
-                for (a, b, r1, r2) in _redo_failures {
-                    info!("Condition failure: {} -> {} (reachable: {}, {})", a, b, r1, r2);
-                }
-            } else {
-                println!("\n=== BEST REDO CUT RESULTS ===");
-                println!("Is Redo Cut Possible: {}", br_is_redo);
-                println!("Minimum Cost: {}", br_min_cost);
-                println!("Edges Removed: {:?}", br_edges_removed);
-                println!("Edges Added: {:?}", br_edges_added);
-                println!("Cost of Added Edges: {}", br_cost_of_added_edges);
-                println!("Cost of Removed Edges: {}", br_cost_of_removed_edges);
-                println!("Set 1: {:?}", br_set1);
-                println!("Set 2: {:?}", br_set2);
-                cuts.push(CutSuggestion {
-                    cut_type: "redo".to_string(),
-                    set1: br_set1,
-                    set2: br_set2,
-                    edges_to_be_added: br_edges_added,
-                    edges_to_be_removed: br_edges_removed,
-                    total_cost: br_min_cost,
-                });
+                info!("Condition failure: {} -> {} (reachable: {}, {})", a, b, r1, r2);
             }
+            continue;
         }
-    } else {
-        info!("Best redo cut not possible!");
+        println!("\n=== BEST {} CUT RESULTS ({}) ===", cut_type.to_uppercase(), finder.name());
+        println!("Minimum Cost: {}", found.total_cost);
+        println!("Cut Edges: {:?}", found.edges_to_be_removed);
+        println!("Added Edges: {:?}", found.edges_to_be_added);
+        println!("Set 1: {:?}", found.set1);
+        println!("Set 2: {:?}", found.set2);
+        let mut sets = maximal_partition(cut_type, &new_dfg, all_activities, found.set1, found.set2, &start_activities, &end_activities).into_iter();
+        cuts.push(CutSuggestion {
+            cut_type: cut_type.to_string(),
+            set1: sets.next().unwrap_or_default(),
+            set2: sets.next().unwrap_or_default(),
+            further_sets: sets.collect(),
+            edges_to_be_added: found.edges_to_be_added,
+            edges_to_be_removed: found.edges_to_be_removed,
+            total_cost: found.total_cost,
+        });
     }
 
      // Create the final result structure
@@ -402,18 +476,18 @@ pub fn find_cuts_for_node_modification(
 ) -> ProcessForest {
     let mut forest = Vec::new();
 
-    let activities: Vec<String> = all_activities.clone().into_iter().collect();
+    let activities: Vec<String> = all_activities.iter().cloned().sorted().collect();
     let n = activities.len();
 
     if n == 1 {
         // Base case: single activity, create a leaf node
         let node = TreeNode {
-            id: Uuid::new_v4().to_string(),
+            id: String::new(),
             label: activities[0].clone(),
             children: Vec::new(),
         };
         forest.push(node);
-        return forest;
+        return with_node_ids(forest);
     }
 
     let filtered_dfg = filter_keep_dfg(&dfg, &all_activities);
@@ -430,7 +504,7 @@ pub fn find_cuts_for_node_modification(
     {
         info!("Exclusive cut found: {:?} (X) {:?}", excl_set1, excl_set2);
         let mut node = TreeNode {
-            id: Uuid::new_v4().to_string(),
+            id: String::new(),
             label: "exclusive".to_string(),
             children: Vec::new(),
         };
@@ -447,7 +521,7 @@ pub fn find_cuts_for_node_modification(
             &end_activities,
         ));
         forest.push(node);
-        return forest;
+        return with_node_ids(forest);
     }
 
     let (seq_set1, seq_set2) = find_sequence_cut(&filtered_dfg, &all_activities);
@@ -458,7 +532,7 @@ pub fn find_cuts_for_node_modification(
     {
         info!("Sequence cut found: {:?} (->) {:?}", seq_set1, seq_set2);
         let mut node = TreeNode {
-            id: Uuid::new_v4().to_string(),
+            id: String::new(),
             label: "sequence".to_string(),
             children: Vec::new(),
         };
@@ -475,7 +549,7 @@ pub fn find_cuts_for_node_modification(
             &end_activities,
         ));
         forest.push(node);
-        return forest;
+        return with_node_ids(forest);
     }
 
     let (is_parallel, para_set1, para_set2) = find_parallel_cut(&filtered_dfg, &all_activities);
@@ -487,7 +561,7 @@ pub fn find_cuts_for_node_modification(
     {
         info!("Parallel cut found: {:?} (||) {:?}", para_set1, para_set2);
         let mut node = TreeNode {
-            id: Uuid::new_v4().to_string(),
+            id: String::new(),
             label: "parallel".to_string(),
             children: Vec::new(),
         };
@@ -504,7 +578,7 @@ pub fn find_cuts_for_node_modification(
             &end_activities,
         ));
         forest.push(node);
-        return forest;
+        return with_node_ids(forest);
     }
 
     let (is_redo, redo_set1, redo_set2) = find_redo_cut(
@@ -527,7 +601,7 @@ pub fn find_cuts_for_node_modification(
     {
         info!("Redo cut found: {:?} (R) {:?}", redo_set1, redo_set2);
         let mut node = TreeNode {
-            id: Uuid::new_v4().to_string(),
+            id: String::new(),
             label: "redo".to_string(),
             children: Vec::new(),
         };
@@ -544,7 +618,7 @@ pub fn find_cuts_for_node_modification(
             &end_activities,
         ));
         forest.push(node);
-        return forest;
+        return with_node_ids(forest);
     }
 
     info!(
@@ -554,7 +628,7 @@ pub fn find_cuts_for_node_modification(
 
     // If no valid cuts are found, create a flower node with disjoint activities as children
     let mut flower_node = TreeNode {
-        id: Uuid::new_v4().to_string(),
+        id: String::new(),
         label: "flower".to_string(),
         children: Vec::new(),
     };
@@ -562,7 +636,7 @@ pub fn find_cuts_for_node_modification(
     // Add all activities as children of the flower node
     for activity in activities {
         let child_node = TreeNode {
-            id: Uuid::new_v4().to_string(),
+            id: String::new(),
             label: activity,
             children: Vec::new(),
         };
@@ -572,7 +646,7 @@ pub fn find_cuts_for_node_modification(
     // Add the flower node to the forest
     forest.push(flower_node);
 
-    return forest;
+    return with_node_ids(forest);
 
 }
 
@@ -583,6 +657,27 @@ fn find_exclusive_choice_cut(
     dfg: &HashMap<(String, String), usize>,
     all_activities: &HashSet<String>,
 ) -> (HashSet<String>, HashSet<String>) {
+    let components = connected_components(dfg, all_activities);
+
+    // Assign first component to set1, rest to set2
+    let mut set1 = HashSet::new();
+    let mut set2 = HashSet::new();
+
+    if !components.is_empty() {
+        set1 = components[0].clone();
+        for comp in components.iter().skip(1) {
+            set2.extend(comp.iter().cloned());
+        }
+    }
+
+    (set1, set2)
+}
+
+// Connected components of the DFG taken as undirected graph, ordered by their smallest activity
+fn connected_components(
+    dfg: &HashMap<(String, String), usize>,
+    all_activities: &HashSet<String>,
+) -> Vec<HashSet<String>> {
     // Step 1: Convert to undirected adjacency list
     let mut undirected_graph: HashMap<String, HashSet<String>> = HashMap::new();
 
@@ -605,7 +700,7 @@ fn find_exclusive_choice_cut(
     let mut visited: HashSet<String> = HashSet::new();
     let mut components: Vec<HashSet<String>> = Vec::new();
 
-    for activity in all_activities {
+    for activity in all_activities.iter().sorted() {
         if !visited.contains(activity) {
             let mut component = HashSet::new();
             let mut queue = VecDeque::new();
@@ -626,27 +721,7 @@ fn find_exclusive_choice_cut(
         }
     }
 
-    // // Step 3: Print disjoint components
-    // info!("Disjoint components:");
-    // for (i, comp) in components.iter().enumerate() {
-    //     info!("Component {}: {:?}", i + 1, comp);
-    // }
-
-    // Step 4: Assign first component to set1, rest to set2
-    let mut set1 = HashSet::new();
-    let mut set2 = HashSet::new();
-
-    if !components.is_empty() {
-        set1 = components[0].clone();
-        for comp in components.iter().skip(1) {
-            set2.extend(comp.iter().cloned());
-        }
-    }
-
-    // Step 5: Print the final sets
-    // info!("\nSet 1: {:?}", set1);
-    // info!("Set 2: {:?}", set2);
-    (set1, set2)
+    components
 }
 
 fn exclusive_cut_condition_check(
@@ -694,6 +769,62 @@ fn find_sequence_cut(
     (set1, set2)
 }
 
+// Maximal sequence cut: SCCs of which neither reaches the other are merged into one group,
+// and the groups are ordered by how much of the DFG they reach. None if the groups are not
+// totally ordered, i.e. some activity of an earlier group does not reach one of a later group
+// or is reached back.
+fn sequence_partition(
+    dfg: &HashMap<(String, String), usize>,
+    all_activities: &HashSet<String>,
+) -> Option<Vec<HashSet<String>>> {
+    let sccs = strongly_connected_components(dfg, all_activities);
+    let (dag, _) = build_scc_dag(&sccs, dfg);
+    let n = sccs.len();
+    let reach: Vec<Vec<bool>> = (0..n)
+        .map(|i| (0..n).map(|j| is_reachable_in_dag(&dag, i, j)).collect())
+        .collect();
+
+    let mut group: Vec<usize> = (0..n).collect();
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if !reach[i][j] && !reach[j][i] && group[i] != group[j] {
+                let (keep, merged) = (group[i], group[j]);
+                for g in group.iter_mut().filter(|g| **g == merged) {
+                    *g = keep;
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for id in group.iter().unique() {
+        groups.push((0..n).filter(|i| group[*i] == *id).collect());
+    }
+    // Earlier groups reach more SCCs
+    groups.sort_by_key(|members| {
+        std::cmp::Reverse((0..n).filter(|j| members.iter().any(|i| reach[*i][*j])).count())
+    });
+
+    for (position, earlier) in groups.iter().enumerate() {
+        for later in &groups[position + 1..] {
+            for i in earlier {
+                for j in later {
+                    if !reach[*i][*j] || reach[*j][*i] {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    Some(
+        groups
+            .iter()
+            .map(|members| members.iter().flat_map(|i| sccs[*i].iter().cloned()).collect())
+            .collect(),
+    )
+}
+
 /// Step 1: Tarjan's Algorithm to find SCCs
 fn strongly_connected_components(
     dfg: &HashMap<(String, String), usize>,
@@ -707,6 +838,10 @@ fn strongly_connected_components(
     for activity in all_activities {
         graph.entry(activity.clone()).or_default();
     }
+    // Visit neighbours in name order so the SCCs come out in the same order every run
+    for neighbors in graph.values_mut() {
+        neighbors.sort();
+    }
 
     // Tarjan’s setup
     let mut index = 0;
@@ -763,7 +898,7 @@ fn strongly_connected_components(
     }
 
     // Run Tarjan's on all nodes
-    for node in all_activities {
+    for node in all_activities.iter().sorted() {
         if !indices.contains_key(node) {
             strongconnect(
                 node,
@@ -822,7 +957,7 @@ pub fn partition_scc_sets(
 
     // Find common activities and remove them from both sets
     let intersection: HashSet<_> = set1.intersection(&set2).cloned().collect();
-    let mut common_activities = intersection.clone();
+    let common_activities = intersection.clone();
 
     for i in &intersection {
         set1.remove(i);
@@ -830,7 +965,7 @@ pub fn partition_scc_sets(
     }
 
     // For each common activity, decide whether to put it in set1 or set2
-    for c in common_activities {
+    for c in common_activities.into_iter().sorted() {
         let mut all_can_reach_and_c_cannot_reach_back = true;
 
         // Check if every activity 't' in set1 can reach 'c', and 'c' cannot reach 't'
@@ -917,7 +1052,7 @@ fn find_parallel_cut(
     let mut set1: HashSet<String> = HashSet::new();
     let mut set2: HashSet<String> = HashSet::new();
 
-    for act in all_activities {
+    for act in all_activities.iter().sorted() {
         if set1.is_empty() {
             set1.insert(act.clone());
             continue;
@@ -949,6 +1084,50 @@ fn find_parallel_cut(
     (true, set1, set2)
 }
 
+// Maximal parallel cut: activities not connected in both directions end up in the same group.
+// Groups without a start or an end activity are merged into the first group that has both;
+// None if fewer than two such groups remain.
+fn parallel_partition(
+    dfg: &HashMap<(String, String), usize>,
+    all_activities: &HashSet<String>,
+    start_activities: &HashSet<String>,
+    end_activities: &HashSet<String>,
+) -> Option<Vec<HashSet<String>>> {
+    let activities: Vec<&String> = all_activities.iter().sorted().collect();
+    let mut group: Vec<usize> = (0..activities.len()).collect();
+    for i in 0..activities.len() {
+        for j in (i + 1)..activities.len() {
+            let (a, b) = (activities[i].clone(), activities[j].clone());
+            let concurrent = dfg.contains_key(&(a.clone(), b.clone())) && dfg.contains_key(&(b, a));
+            if !concurrent && group[i] != group[j] {
+                let (keep, merged) = (group[i], group[j]);
+                for g in group.iter_mut().filter(|g| **g == merged) {
+                    *g = keep;
+                }
+            }
+        }
+    }
+
+    let mut complete: Vec<HashSet<String>> = Vec::new();
+    let mut incomplete: HashSet<String> = HashSet::new();
+    for id in group.iter().unique() {
+        let members: HashSet<String> = (0..activities.len())
+            .filter(|i| group[*i] == *id)
+            .map(|i| activities[i].clone())
+            .collect();
+        if members.is_disjoint(start_activities) || members.is_disjoint(end_activities) {
+            incomplete.extend(members);
+        } else {
+            complete.push(members);
+        }
+    }
+    if complete.len() < 2 {
+        return None;
+    }
+    complete[0].extend(incomplete);
+    Some(complete)
+}
+
 fn parallel_cut_condition_check(
     dfg: &HashMap<(String, String), usize>,
     set1: &HashSet<String>,
@@ -1223,3 +1402,178 @@ pub fn is_reachable_before_end_activity(
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dfg(edges: &[(&str, &str)]) -> HashMap<(String, String), usize> {
+        edges.iter().map(|(from, to)| ((from.to_string(), to.to_string()), 1)).collect()
+    }
+
+    fn set(activities: &[&str]) -> HashSet<String> {
+        activities.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn traces(traces: &[(&str, &[&str])]) -> Vec<ObjectTrace> {
+        traces
+            .iter()
+            .map(|(object_type, activities)| ObjectTrace {
+                object_type: object_type.to_string(),
+                activities: activities.iter().map(|a| a.to_string()).collect(),
+                throughput_time: 0.0,
+            })
+            .collect()
+    }
+
+    // The forest written as nested operators, e.g. sequence(a, exclusive(b, tau))
+    fn shape(forest: &[TreeNode]) -> String {
+        forest
+            .iter()
+            .map(|node| {
+                if node.children.is_empty() {
+                    node.label.clone()
+                } else {
+                    format!("{}({})", node.label, shape(&node.children))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // Branches of the suggested cut of `cut_type`, each sorted, and the number of further sets
+    fn suggested(
+        edges: &[(&str, &str)],
+        activities: &[&str],
+        start: &[&str],
+        end: &[&str],
+        cut_type: &str,
+    ) -> (Vec<Vec<String>>, usize) {
+        let suggestions = find_best_possible_cuts(
+            &dfg(edges),
+            &set(activities),
+            &set(start),
+            &set(end),
+            &HashMap::new(),
+            &CutStrategies::default(),
+        );
+        let cut = suggestions.cuts.iter().find(|cut| cut.cut_type == cut_type).unwrap();
+        let branches = cut.sets().map(|branch| branch.iter().sorted().cloned().collect()).collect();
+        (branches, cut.further_sets.len())
+    }
+
+    #[test]
+    fn objects_skipping_everything_make_the_process_optional() {
+        // Items do none of the activities and so are unrelated rather than skipping
+        let log = traces(&[("order", &["a", "b"]), ("order", &[]), ("item", &["x"])]);
+        let forest = find_cuts_start_with_traces(&dfg(&[("a", "b")]), &set(&["a", "b"]), &set(&["a"]), &set(&["b"]), &log);
+        assert_eq!(shape(&forest), "exclusive(sequence(a, b), tau)");
+    }
+
+    #[test]
+    fn activity_once_per_trace_runs_in_parallel() {
+        let log = traces(&[("order", &["a", "b", "a", "c", "a"]), ("order", &["a", "c", "a", "b", "a"])]);
+        let hub = dfg(&[("a", "b"), ("b", "a"), ("a", "c"), ("c", "a")]);
+        let forest = find_cuts_start_with_traces(&hub, &set(&["a", "b", "c"]), &set(&["a"]), &set(&["a"]), &log);
+        assert_eq!(shape(&forest), "parallel(b, parallel(c, a))");
+    }
+
+    #[test]
+    fn activity_concurrent_runs_in_parallel() {
+        // Without a and the edges through it, b and c are concurrent
+        let hub = dfg(&[("a", "b"), ("b", "a"), ("a", "c"), ("c", "a")]);
+        let forest = find_cuts_start(&hub, &set(&["a", "b", "c"]), &set(&["a"]), &set(&["a"]));
+        assert_eq!(shape(&forest), "parallel(a, parallel(b, c))");
+    }
+
+    #[test]
+    fn strict_tau_loop_drops_the_end_to_start_edges() {
+        let forest = find_cuts_start(&dfg(&[("a", "b"), ("b", "a")]), &set(&["a", "b"]), &set(&["a"]), &set(&["b"]));
+        assert_eq!(shape(&forest), "redo(sequence(a, b), tau)");
+    }
+
+    #[test]
+    fn tau_loop_drops_the_edges_into_start_activities() {
+        // a -> b restarts without passing the end activity c
+        let hub = dfg(&[("a", "b"), ("b", "a"), ("a", "c"), ("c", "a")]);
+        let forest = find_cuts_start(&hub, &set(&["a", "b", "c"]), &set(&["b"]), &set(&["c"]));
+        assert_eq!(shape(&forest), "redo(sequence(b, redo(sequence(a, exclusive(c, tau)), tau)), tau)");
+    }
+
+    #[test]
+    fn exclusive_cut_widens_to_every_component() {
+        let all = ["a", "b", "c", "d", "e"];
+        let (branches, further) = suggested(&[], &all, &all, &all, "exclusive");
+        assert_eq!(branches, vec![vec!["a"], vec!["b"], vec!["c"], vec!["d"], vec!["e"]]);
+        assert_eq!(further, 3);
+    }
+
+    #[test]
+    fn sequence_cut_widens_to_every_step_in_order() {
+        let (branches, further) = suggested(&[("a", "b"), ("b", "c")], &["a", "b", "c"], &["a"], &["c"], "sequence");
+        assert_eq!(branches, vec![vec!["a"], vec!["b"], vec!["c"]]);
+        assert_eq!(further, 1);
+    }
+
+    #[test]
+    fn parallel_cut_widens_to_every_concurrent_group() {
+        let all = ["a", "b", "c"];
+        let edges = [("a", "b"), ("b", "a"), ("a", "c"), ("c", "a"), ("b", "c"), ("c", "b")];
+        let (branches, further) = suggested(&edges, &all, &all, &all, "parallel");
+        assert_eq!(branches, vec![vec!["a"], vec!["b"], vec!["c"]]);
+        assert_eq!(further, 1);
+    }
+
+    #[test]
+    fn parallel_groups_without_start_or_end_join_the_first_group() {
+        // d is concurrent to everything but can neither start nor end the process
+        let edges = [
+            ("a", "b"), ("b", "a"), ("a", "c"), ("c", "a"), ("b", "c"), ("c", "b"),
+            ("a", "d"), ("d", "a"), ("b", "d"), ("d", "b"), ("c", "d"), ("d", "c"),
+        ];
+        let abc = ["a", "b", "c"];
+        let (branches, further) = suggested(&edges, &["a", "b", "c", "d"], &abc, &abc, "parallel");
+        assert_eq!(branches, vec![vec!["a", "d"], vec!["b"], vec!["c"]]);
+        assert_eq!(further, 1);
+    }
+
+    #[test]
+    fn cuts_that_cannot_be_widened_stay_binary() {
+        // b and c are not concurrent, so they form one group
+        let all = ["a", "b", "c"];
+        let (branches, further) = suggested(&[("a", "b"), ("b", "a"), ("a", "c"), ("c", "a")], &all, &all, &all, "parallel");
+        assert_eq!(branches, vec![vec!["a"], vec!["b", "c"]]);
+        assert_eq!(further, 0);
+
+        // b and c loop, so they are one step
+        let (branches, further) = suggested(&[("a", "b"), ("b", "c"), ("c", "b")], &all, &["a"], &["c"], "sequence");
+        assert_eq!(branches, vec![vec!["a"], vec!["b", "c"]]);
+        assert_eq!(further, 0);
+    }
+
+    #[test]
+    fn objects_skipping_a_branch_make_it_optional() {
+        // Nothing in the DFG tells that b can be skipped, the second order does
+        let log = traces(&[("order", &["a", "b"]), ("order", &["a"])]);
+        let forest = find_cuts_start_with_traces(&dfg(&[("a", "b")]), &set(&["a", "b"]), &set(&["a"]), &set(&["b"]), &log);
+        assert_eq!(shape(&forest), "sequence(a, exclusive(b, tau))");
+        let forest = find_cuts_start(&dfg(&[("a", "b")]), &set(&["a", "b"]), &set(&["a"]), &set(&["b"]));
+        assert_eq!(shape(&forest), "sequence(a, b)");
+    }
+
+    #[test]
+    fn selected_cuts_split_the_traces() {
+        let edges = dfg(&[("a", "b"), ("b", "c")]);
+        // x lies outside the flower; the last order skips c
+        let log = traces(&[("order", &["x", "a", "b", "c"]), ("order", &["a", "b"]), ("order", &["x"])]);
+        let sequence = [set(&["a"]), set(&["b", "c"])];
+        let branches = find_cuts_below_cut(&edges, "sequence", &sequence, &set(&["a"]), &set(&["c"]), &log);
+        assert_eq!(branches.iter().map(|forest| shape(forest)).collect::<Vec<_>>(), vec!["a", "sequence(b, exclusive(c, tau))"]);
+
+        // Orders taking one branch of a choice do not skip the other
+        let log = traces(&[("order", &["a"]), ("order", &["b", "c"])]);
+        let exclusive = [set(&["a"]), set(&["b", "c"])];
+        let branches = find_cuts_below_cut(&dfg(&[("b", "c")]), "exclusive", &exclusive, &set(&["a", "b"]), &set(&["a", "c"]), &log);
+        assert_eq!(branches.iter().map(|forest| shape(forest)).collect::<Vec<_>>(), vec!["a", "sequence(b, c)"]);
+    }
+}
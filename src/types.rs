@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, FixedOffset};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::cut_finders::CutStrategyNames;
+use crate::interaction_patterns::PatternSupport;
+use crate::ocel_filter::OCELFilter;
+use crate::Params;
 
 // OCEL 2.0 structures
 
@@ -49,6 +54,7 @@ pub struct OCELEvent {
     #[serde(rename = "type")]
     pub event_type: String,
     /// `DateTime` when event occured
+    #[serde(deserialize_with = "robust_timestamp_parsing")]
     pub time: DateTime<FixedOffset>,
     /// Event attributes
     #[serde(default)]
@@ -130,39 +136,59 @@ where
     D: serde::Deserializer<'de>,
 {
     let time: String = Deserialize::deserialize(deserializer)?;
-    if let Ok(dt) = DateTime::parse_from_rfc3339(&time) {
-        return Ok(dt);
+    parse_timestamp(&time).ok_or_else(|| serde::de::Error::custom("Unexpected Date Format"))
+}
+
+// Activity sets go out sorted, so that the same response serializes the same way every run
+fn sorted_set<S>(set: &HashSet<String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_seq(set.iter().collect::<BTreeSet<_>>())
+}
+
+fn sorted_sets<S>(sets: &[HashSet<String>], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_seq(sets.iter().map(|set| set.iter().collect::<BTreeSet<_>>()))
+}
+
+/// Parses the timestamp formats found in the wild in OCEL exports
+pub fn parse_timestamp(time: &str) -> Option<DateTime<FixedOffset>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(time) {
+        return Some(dt);
     }
-    if let Ok(dt) = DateTime::parse_from_rfc2822(&time) {
-        return Ok(dt);
+    if let Ok(dt) = DateTime::parse_from_rfc2822(time) {
+        return Some(dt);
     }
     // eprintln!("Encountered weird datetime format: {:?}", time);
 
     // Some logs have this date: "2023-10-06 09:30:21.890421"
     // Assuming that this is UTC
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(&time, "%F %T%.f") {
-        return Ok(dt.and_utc().into());
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(time, "%F %T%.f") {
+        return Some(dt.and_utc().into());
     }
 
     // Also handle "2024-10-02T07:55:15.348555" as well as "2022-01-09T15:00:00"
     // Assuming UTC time zone
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(&time, "%FT%T%.f") {
-        return Ok(dt.and_utc().into());
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(time, "%FT%T%.f") {
+        return Some(dt.and_utc().into());
     }
 
     // export_path
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(&time, "%F %T UTC") {
-        return Ok(dt.and_utc().into());
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(time, "%F %T UTC") {
+        return Some(dt.and_utc().into());
     }
 
     // Who made me do this? 🫣
     // Some logs have this date: "Mon Apr 03 2023 12:08:18 GMT+0200 (Mitteleuropäische Sommerzeit)"
     // Below ignores the first "Mon " part (%Z) parses the rest (only if "GMT") and then parses the timezone (+0200)
     // The rest of the input is ignored
-    if let Ok((dt, _)) = DateTime::parse_and_remainder(&time, "%Z %b %d %Y %T GMT%z") {
-        return Ok(dt);
+    if let Ok((dt, _)) = DateTime::parse_and_remainder(time, "%Z %b %d %Y %T GMT%z") {
+        return Some(dt);
     }
-    Err(serde::de::Error::custom("Unexpected Date Format"))
+    None
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -175,6 +201,15 @@ pub struct TreeNode {
 
 pub type ProcessForest = Vec<TreeNode>;
 
+/// Activities of one object in time order, as used by the conformance checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectTrace {
+    pub object_type: String,
+    pub activities: Vec<String>,
+    /// Seconds from the first to the last event
+    pub throughput_time: f64,
+}
+
 
 // For format conversion of DFG to be sent a JSON response
 #[derive(Serialize, Deserialize)]
@@ -190,24 +225,204 @@ pub struct Edge {
     pub target: String,
     pub label: String,
     pub cost: usize, 
+    /// Raw directly-follows count, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+    /// Number of objects whose trace has this edge, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objects: Option<usize>,
+    /// `ceil(count / total * 100)`, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<usize>,
+    /// Waiting time between the two activities, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_time: Option<WaitingTimeStats>,
+    /// Share of every object type in the edge, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_types: Option<BTreeMap<String, ObjectTypeEdge>>,
+}
+
+/// Weights of one edge of the divergence-free DFG
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DfgEdge {
+    /// Number of times the activities directly follow each other
+    pub count: usize,
+    /// Number of distinct objects in whose trace they directly follow each other
+    pub objects: usize,
+    /// `count` as a rounded-up percentage of all directly-follows pairs
+    pub percentage: usize,
+}
+
+/// Summary of a set of values: mean, extremes and percentiles
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Distribution {
+    pub mean: f64,
+    pub median: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p25: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub p95: f64,
+}
+
+impl Distribution {
+    /// Summary of the given values; `None` if there are none. Percentiles are interpolated
+    /// linearly between the closest ranks.
+    pub fn from_values(mut values: Vec<f64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f64| {
+            let rank = p / 100.0 * (values.len() - 1) as f64;
+            let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+            values[lower] + (values[upper] - values[lower]) * (rank - lower as f64)
+        };
+        Some(Distribution {
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            median: percentile(50.0),
+            min: values[0],
+            max: values[values.len() - 1],
+            p25: percentile(25.0),
+            p75: percentile(75.0),
+            p90: percentile(90.0),
+            p95: percentile(95.0),
+        })
+    }
+}
+
+/// Time in seconds between the two events of the directly-follows pairs of an edge
+pub type WaitingTimeStats = Distribution;
+
+/// Part of a [`DfgEdge`] produced by the objects of one type
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ObjectTypeEdge {
+    pub count: usize,
+    pub objects: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_time: Option<WaitingTimeStats>,
+}
+
+/// Edge of an [`OCDfg`]: the merged weights and their breakdown by object type
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OCDfgEdge {
+    pub weights: DfgEdge,
+    pub waiting_time: Option<WaitingTimeStats>,
+    pub object_types: BTreeMap<String, ObjectTypeEdge>,
+}
+
+/// Divergence-free DFG edges with their weights, start activities and end activities
+pub type WeightedDfg = (HashMap<(String, String), DfgEdge>, HashSet<String>, HashSet<String>);
+
+/// Object-centric DFG: the divergence-free DFG with every edge attributed to the object types
+/// whose traces produced it, and the start and end activities of every object type
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OCDfg {
+    pub edges: HashMap<(String, String), OCDfgEdge>,
+    pub start_activities: BTreeMap<String, BTreeSet<String>>,
+    pub end_activities: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl OCDfg {
+    /// Projects onto the single-graph view the cut algorithms work on: the merged edge
+    /// weights and the start and end activities of all object types together
+    pub fn merged(&self) -> WeightedDfg {
+        let dfg = self.edges.iter().map(|(key, edge)| (key.clone(), edge.weights)).collect();
+        let start_activities = self.start_activities.values().flatten().cloned().collect();
+        let end_activities = self.end_activities.values().flatten().cloned().collect();
+        (dfg, start_activities, end_activities)
+    }
+}
+
+/// Which [`DfgEdge`] weight the cut search works with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EdgeWeight {
+    Count,
+    Objects,
+    /// The scale the cost of adding edges is computed on; for the other weights it is
+    /// rescaled, see [`crate::divergence_free_dfg::scale_costs_to_weight`]
+    #[default]
+    Percentage,
+}
+
+impl EdgeWeight {
+    /// Parses the `weight` query parameter (`count`, `objects` or `percentage`).
+    pub fn from_param(weight: Option<&str>) -> Result<Self, String> {
+        match weight.unwrap_or("percentage") {
+            "count" => Ok(EdgeWeight::Count),
+            "objects" => Ok(EdgeWeight::Objects),
+            "percentage" => Ok(EdgeWeight::Percentage),
+            other => Err(format!("Unknown weight '{}', expected count, objects or percentage", other)),
+        }
+    }
+
+    pub fn of(&self, edge: &DfgEdge) -> usize {
+        match self {
+            EdgeWeight::Count => edge.count,
+            EdgeWeight::Objects => edge.objects,
+            EdgeWeight::Percentage => edge.percentage,
+        }
+    }
+}
+
+/// How infrequent DFG edges are dropped before discovery (the `noise` query parameter)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NoiseFilter {
+    /// Drops edges not above `n` times the total edge weight
+    #[default]
+    Global,
+    /// Drops outgoing edges below `n` times the strongest outgoing edge of the same
+    /// activity, as Inductive Miner - infrequent does
+    PerActivity,
+}
+
+impl NoiseFilter {
+    /// Parses the `noise` query parameter (`global` or `activity`).
+    pub fn from_param(noise: Option<&str>) -> Result<Self, String> {
+        match noise.unwrap_or("global") {
+            "global" => Ok(NoiseFilter::Global),
+            "activity" => Ok(NoiseFilter::PerActivity),
+            other => Err(format!("Unknown noise filter '{}', expected global or activity", other)),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Graph {
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    /// Start activities per object type, when known
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub start_activities: BTreeMap<String, BTreeSet<String>>,
+    /// End activities per object type, when known
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub end_activities: BTreeMap<String, BTreeSet<String>>,
 }
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CutSuggestion {
     pub cut_type: String,
+    #[serde(serialize_with = "sorted_set")]
     pub set1: HashSet<String>,
+    #[serde(serialize_with = "sorted_set")]
     pub set2: HashSet<String>,
+    /// Branches of an n-ary cut after set1 and set2, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty", serialize_with = "sorted_sets")]
+    pub further_sets: Vec<HashSet<String>>,
     pub edges_to_be_added: Vec<(String, String, usize)>,
     pub edges_to_be_removed: Vec<(String, String, usize)>,
     pub total_cost: usize
 }
+
+impl CutSuggestion {
+    /// All branches of the cut in order
+    pub fn sets(&self) -> impl Iterator<Item = &HashSet<String>> {
+        [&self.set1, &self.set2].into_iter().chain(self.further_sets.iter())
+    }
+}
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CutSuggestionsList {
+    #[serde(serialize_with = "sorted_set")]
     pub all_activities: HashSet<String>,
     pub cuts: Vec<CutSuggestion>
 }
@@ -223,7 +438,9 @@ pub struct EdgeModification {
 pub struct APIResponse {
     pub OCPT: serde_json::Value,
     pub dfg: serde_json::Value,
+    #[serde(serialize_with = "sorted_set")]
     pub start_activities: HashSet<String>,
+    #[serde(serialize_with = "sorted_set")]
     pub end_activities: HashSet<String>,
     pub is_perfectly_cut: bool,
     pub cut_suggestions_list: CutSuggestionsList,
@@ -231,6 +448,16 @@ pub struct APIResponse {
     pub total_edges_added: Vec<(String, String, usize)>,
     pub edge_modifications: Vec<EdgeModification>,
     pub cost_to_add_edges: serde_json::Value,
+    /// Edges dropped by the noise filter at discovery time
+    pub noise_removed_edges: Vec<(String, String, usize)>,
+    /// Filter the log was reduced with before discovery
+    pub filter: OCELFilter,
+    /// Share of events each interaction pattern holds for, per activity/object type pair
+    pub interaction_pattern_support: Vec<PatternSupport>,
+    /// Cut finders the suggestions were found with, for later steps to use again
+    pub cut_strategies: CutStrategyNames,
+    /// Query parameters of the discovery, for later steps to prepare the log the same way
+    pub discovery_params: Params,
     pub precision: f64,
     pub fitness: f64,
     pub f_score: f64,
@@ -250,6 +477,16 @@ pub struct CutSelectedAPIRequest {
     #[serde(default)]
     pub edge_modifications: Vec<EdgeModification>,
     pub cost_to_add_edges: serde_json::Value,
+    #[serde(default)]
+    pub noise_removed_edges: Vec<(String, String, usize)>,
+    #[serde(default)]
+    pub filter: OCELFilter,
+    #[serde(default)]
+    pub interaction_pattern_support: Vec<PatternSupport>,
+    #[serde(default)]
+    pub cut_strategies: CutStrategyNames,
+    #[serde(default)]
+    pub discovery_params: Params,
 }
 
 #[derive(serde::Deserialize)]
@@ -264,6 +501,16 @@ pub struct ModifyNodeAPIRequest {
     #[serde(default)]
     pub edge_modifications: Vec<EdgeModification>,
     pub cost_to_add_edges: serde_json::Value,
+    #[serde(default)]
+    pub noise_removed_edges: Vec<(String, String, usize)>,
+    #[serde(default)]
+    pub filter: OCELFilter,
+    #[serde(default)]
+    pub interaction_pattern_support: Vec<PatternSupport>,
+    #[serde(default)]
+    pub cut_strategies: CutStrategyNames,
+    #[serde(default)]
+    pub discovery_params: Params,
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::types::ObjectTrace;
+
+/// One distinct activity sequence of an object type
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub activities: Vec<String>,
+    /// Number of objects whose trace is this sequence
+    pub frequency: usize,
+    /// Share of the traces of the object type that are this sequence
+    pub coverage: f64,
+    /// Mean seconds from the first to the last event
+    pub average_throughput_time: f64,
+}
+
+/// Variants of one object type, most frequent first
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ObjectTypeVariants {
+    pub object_type: String,
+    /// Number of traces of the object type
+    pub traces: usize,
+    /// Number of distinct variants, also when only the most frequent are listed
+    pub variant_count: usize,
+    pub variants: Vec<Variant>,
+}
+
+/// Groups per-object traces (see [`crate::add_self_loops::get_traces`]) into variants per
+/// object type. Object types are sorted by name; variants by frequency, ties by activities.
+pub fn get_variants(traces: &[ObjectTrace]) -> Vec<ObjectTypeVariants> {
+    // object type -> activity sequence -> (frequency, summed throughput time)
+    let mut grouped: HashMap<&str, HashMap<&[String], (usize, f64)>> = HashMap::new();
+    for trace in traces {
+        let variant = grouped
+            .entry(trace.object_type.as_str())
+            .or_default()
+            .entry(trace.activities.as_slice())
+            .or_default();
+        variant.0 += 1;
+        variant.1 += trace.throughput_time;
+    }
+
+    let mut per_type: Vec<ObjectTypeVariants> = grouped
+        .into_iter()
+        .map(|(object_type, sequences)| {
+            let total: usize = sequences.values().map(|(frequency, _)| frequency).sum();
+            let mut variants: Vec<Variant> = sequences
+                .into_iter()
+                .map(|(activities, (frequency, throughput_time))| Variant {
+                    activities: activities.to_vec(),
+                    frequency,
+                    coverage: frequency as f64 / total as f64,
+                    average_throughput_time: throughput_time / frequency as f64,
+                })
+                .collect();
+            variants.sort_by(|a, b| b.frequency.cmp(&a.frequency).then_with(|| a.activities.cmp(&b.activities)));
+            ObjectTypeVariants { object_type: object_type.to_string(), traces: total, variant_count: variants.len(), variants }
+        })
+        .collect();
+    per_type.sort_by(|a, b| a.object_type.cmp(&b.object_type));
+    per_type
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::add_self_loops::get_traces_from_index;
+    use crate::build_relations_fns::build_relations;
+    use crate::event_log_index::TieBreak;
+    use crate::interaction_patterns::get_interaction_patterns;
+    use crate::types::{OCELEvent, OCELObject, OCELRelationship, OCEL};
+
+    #[test]
+    fn traces_of_divergent_types_are_not_counted() {
+        let start = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap();
+        let mut events = Vec::new();
+        let mut event = |activity: &str, objects: &[&str]| {
+            let minutes = events.len() as i64;
+            events.push(OCELEvent {
+                id: format!("e{}", events.len()),
+                event_type: activity.to_string(),
+                time: start + chrono::Duration::minutes(minutes),
+                attributes: Vec::new(),
+                relationships: objects
+                    .iter()
+                    .map(|object| OCELRelationship { object_id: object.to_string(), qualifier: String::new() })
+                    .collect(),
+            });
+        };
+        // c1 places and pays two orders, which makes both activities divergent for customers
+        for (order, customer) in [("o1", "c1"), ("o2", "c1"), ("o3", "c2")] {
+            event("place", &[order, customer]);
+            event("pay", &[order, customer]);
+        }
+        event("ship", &["o3"]);
+        let objects = ["o1", "o2", "o3", "c1", "c2"]
+            .iter()
+            .map(|id| OCELObject {
+                id: id.to_string(),
+                object_type: if id.starts_with('o') { "order" } else { "customer" }.to_string(),
+                attributes: Vec::new(),
+                relationships: Vec::new(),
+            })
+            .collect();
+        let ocel = OCEL { event_types: Vec::new(), object_types: Vec::new(), events, objects };
+
+        let index = build_relations(&ocel.events, &ocel.objects, &TieBreak::default());
+        let divergent = get_interaction_patterns(&index).0;
+        assert_eq!(divergent["pay"], ["customer"]);
+        let variants = get_variants(&get_traces_from_index(&index, &divergent));
+
+        assert_eq!(variants.len(), 1);
+        let orders = &variants[0];
+        assert_eq!((orders.object_type.as_str(), orders.traces, orders.variant_count), ("order", 3, 2));
+        let counted: Vec<(usize, usize)> = orders.variants.iter().map(|v| (v.activities.len(), v.frequency)).collect();
+        assert_eq!(counted, [(2, 2), (3, 1)]);
+        assert_eq!(orders.variants[1].average_throughput_time, 120.0);
+    }
+}